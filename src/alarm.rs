@@ -0,0 +1,164 @@
+use crate::device::{format_duration, get_child_element_text, parse_duration, xml_escape, Speaker};
+use crate::error::*;
+use crate::service::Service;
+
+use std::time::Duration;
+
+use failure::Error;
+use xmltree::{Element, XMLNode};
+
+/// An alarm set up in the household.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alarm {
+    pub id: u32,
+    /// Time the alarm goes off at in hh:mm:ss
+    pub start_time: String,
+    /// How long the alarm plays for before stopping
+    pub duration: Duration,
+    /// When the alarm repeats (eg. ONCE, DAILY, WEEKDAYS, WEEKENDS or ON_0123456 for specific days)
+    pub recurrence: String,
+    pub enabled: bool,
+    /// UUID of the speaker the alarm plays on
+    pub room_uuid: String,
+    pub program_uri: String,
+    pub program_metadata: String,
+    /// Play mode used for the alarm (eg. NORMAL or SHUFFLE_NOREPEAT)
+    pub play_mode: String,
+    pub volume: u8,
+    /// Whether the alarm also plays on the speakers grouped with `room_uuid`
+    pub include_linked_zones: bool,
+}
+
+/// A snapshot of the household's alarm clock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlarmClockInfo {
+    pub alarms: Vec<Alarm>,
+    /// Incremented whenever an alarm is changed
+    pub update_id: u32,
+    /// Time of day the alarm list is refreshed at in hh:mm:ss
+    pub daily_refresh_time: String,
+}
+
+fn get_attribute<'a>(el: &'a Element, name: &str) -> Result<&'a str, Error> {
+    el.attributes
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| SonosError::ParseError(format!("missing {} attribute on Alarm", name)).into())
+}
+
+fn parse_alarm(el: &Element) -> Result<Alarm, Error> {
+    Ok(Alarm {
+        id: get_attribute(el, "ID")?.parse()?,
+        start_time: get_attribute(el, "StartTime")?.to_string(),
+        duration: parse_duration(get_attribute(el, "Duration")?, "Duration")?,
+        recurrence: get_attribute(el, "Recurrence")?.to_string(),
+        enabled: get_attribute(el, "Enabled")? == "1",
+        room_uuid: get_attribute(el, "RoomUUID")?.to_string(),
+        program_uri: get_attribute(el, "ProgramURI")?.to_string(),
+        program_metadata: get_attribute(el, "ProgramMetaData")?.to_string(),
+        play_mode: get_attribute(el, "PlayMode")?.to_string(),
+        volume: get_attribute(el, "Volume")?.parse()?,
+        include_linked_zones: get_attribute(el, "IncludeLinkedZones")? == "1",
+    })
+}
+
+impl Speaker {
+    /// Get the alarm list along with its version, which changes whenever an alarm does.
+    pub(crate) async fn list_alarms(&self) -> Result<(Vec<Alarm>, String), Error> {
+        let resp = self.soap(
+            &Service::ALARM_CLOCK,
+            "ListAlarms",
+            "",
+            false,
+        ).await?;
+
+        let list = Element::parse(get_child_element_text(&resp, "CurrentAlarmList")?.as_bytes())?;
+        let alarms = list
+            .children
+            .iter()
+            .filter_map(XMLNode::as_element)
+            .filter(|el| el.name == "Alarm")
+            .map(parse_alarm)
+            .collect::<Result<_, Error>>()?;
+
+        Ok((alarms, get_child_element_text(&resp, "CurrentAlarmListVersion")?.into_owned()))
+    }
+
+    /// Get every alarm in the household.
+    pub async fn alarms(&self) -> Result<Vec<Alarm>, Error> {
+        Ok(self.list_alarms().await?.0)
+    }
+
+    /// Get the household's alarms along with the alarm list's update ID and daily refresh time.
+    pub async fn get_alarm_clock_info(&self) -> Result<AlarmClockInfo, Error> {
+        let (alarms, version) = self.list_alarms().await?;
+
+        // the version is of the form RINCON_000E58000000001400:12
+        let update_id = version
+            .rsplit(':')
+            .next()
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| SonosError::ParseError(format!("invalid alarm list version {}", version)))?;
+
+        let resp = self.soap(
+            &Service::ALARM_CLOCK,
+            "GetDailyIndexRefreshTime",
+            "",
+            false,
+        ).await?;
+
+        Ok(AlarmClockInfo {
+            alarms,
+            update_id,
+            daily_refresh_time: get_child_element_text(&resp, "CurrentDailyIndexRefreshTime")?.into_owned(),
+        })
+    }
+
+    /// Replace the alarm with the same ID as `alarm`.
+    async fn update_alarm(&self, alarm: &Alarm) -> Result<(), Error> {
+        self.soap(
+            &Service::ALARM_CLOCK,
+            "UpdateAlarm",
+            &format!(
+                "<ID>{}</ID><StartLocalTime>{}</StartLocalTime><Duration>{}</Duration>\
+                 <Recurrence>{}</Recurrence><Enabled>{}</Enabled><RoomUUID>{}</RoomUUID>\
+                 <ProgramURI>{}</ProgramURI><ProgramMetaData>{}</ProgramMetaData>\
+                 <PlayMode>{}</PlayMode><Volume>{}</Volume>\
+                 <IncludeLinkedZones>{}</IncludeLinkedZones>",
+                alarm.id,
+                alarm.start_time,
+                format_duration(&alarm.duration),
+                alarm.recurrence,
+                alarm.enabled as u8,
+                alarm.room_uuid,
+                xml_escape(&alarm.program_uri),
+                xml_escape(&alarm.program_metadata),
+                alarm.play_mode,
+                alarm.volume,
+                alarm.include_linked_zones as u8,
+            ),
+            false,
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Whether any alarm in the household is enabled.
+    pub async fn household_alarm_enabled(&self) -> Result<bool, Error> {
+        Ok(self.alarms().await?.iter().any(|alarm| alarm.enabled))
+    }
+
+    /// Enable or disable every alarm in the household at once (eg. while on holiday).
+    ///
+    /// Sonos has no household-wide switch for alarms so each alarm is updated individually,
+    /// alarms already in the requested state are left alone.
+    pub async fn set_household_alarm_enabled(&self, enabled: bool) -> Result<(), Error> {
+        for alarm in self.alarms().await? {
+            if alarm.enabled != enabled {
+                self.update_alarm(&Alarm { enabled, ..alarm }).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
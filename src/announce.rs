@@ -0,0 +1,121 @@
+use crate::device::{get_child_element_text, get_child_text, xml_escape, Speaker, TransportState};
+use crate::service::Service;
+
+use std::time::Duration;
+
+use failure::Error;
+use futures::future;
+
+/// How long an announcement is given to finish playing before the group is restored anyway.
+const ANNOUNCEMENT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What a group coordinator was playing before an announcement interrupted it.
+struct Snapshot {
+    uri: String,
+    metadata: String,
+    /// Position in the queue, 0 if not playing from the queue
+    track: u64,
+    /// How far into the track playback was in hh:mm:ss
+    position: String,
+    playing: bool,
+}
+
+impl Speaker {
+    /// Record what this speaker is playing so it can be restored with [Speaker::restore].
+    async fn snapshot(&self) -> Result<Snapshot, Error> {
+        let media = self.soap(
+            &Service::AV_TRANSPORT,
+            "GetMediaInfo",
+            "<InstanceID>0</InstanceID>",
+            true,
+        ).await?;
+        let position = self.get_position_info_raw().await?;
+
+        Ok(Snapshot {
+            uri: get_child_text(&media, "CurrentURI").unwrap_or_default(),
+            metadata: get_child_text(&media, "CurrentURIMetaData").unwrap_or_default(),
+            track: get_child_element_text(&position, "Track")?.parse()?,
+            position: get_child_text(&position, "RelTime").unwrap_or_default(),
+            playing: self.transport_state().await? == TransportState::Playing,
+        })
+    }
+
+    /// Go back to playing what was recorded in `snapshot`.
+    async fn restore(&self, snapshot: &Snapshot) -> Result<(), Error> {
+        if snapshot.uri.is_empty() {
+            return Ok(());
+        }
+
+        self.soap(
+            &Service::AV_TRANSPORT,
+            "SetAVTransportURI",
+            &format!(
+                "<InstanceID>0</InstanceID><CurrentURI>{}</CurrentURI><CurrentURIMetaData>{}</CurrentURIMetaData>",
+                xml_escape(&snapshot.uri),
+                xml_escape(&snapshot.metadata)
+            ),
+            true,
+        ).await?;
+
+        if snapshot.uri.starts_with("x-rincon-queue:") && snapshot.track > 0 {
+            for (unit, target) in &[("TRACK_NR", snapshot.track.to_string()), ("REL_TIME", snapshot.position.clone())] {
+                self.soap(
+                    &Service::AV_TRANSPORT,
+                    "Seek",
+                    &format!("<InstanceID>0</InstanceID><Unit>{}</Unit><Target>{}</Target>", unit, target),
+                    true,
+                ).await?;
+            }
+        }
+
+        if snapshot.playing {
+            self.play().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Play a notification (eg. a doorbell chime) on every speaker in this speaker's group at the
+    /// given volume, then go back to what the group was playing before.
+    ///
+    /// The volume of every member of the group is restored afterwards. If the announcement is
+    /// still playing after a minute the group is restored anyway.
+    pub async fn announce_to_group(&self, uri: &str, volume: u8) -> Result<(), Error> {
+        let coordinator = self.coordinator_speaker().await?;
+
+        let members = future::try_join_all(
+            self.get_all_zone_members()
+                .await?
+                .into_iter()
+                .map(|member| {
+                    let coordinator = coordinator.clone();
+
+                    async move {
+                        if member.uuid == coordinator.uuid {
+                            Ok::<_, Error>(coordinator)
+                        } else {
+                            Speaker::from_ip(member.ip).await
+                        }
+                    }
+                }),
+        ).await?;
+
+        let volumes = future::try_join_all(members.iter().map(Speaker::volume)).await?;
+        let snapshot = coordinator.snapshot().await?;
+
+        let announced = async {
+            future::try_join_all(members.iter().map(|member| member.set_volume(volume))).await?;
+            coordinator.play_uri_and_wait(uri, ANNOUNCEMENT_TIMEOUT).await
+        }.await;
+
+        // restore the group even if the announcement failed part way through, and every member's
+        // volume even if some can't be set
+        let restored = coordinator.restore(&snapshot).await;
+        let volumes_restored = future::join_all(
+            members.iter().zip(volumes).map(|(member, volume)| member.set_volume(volume)),
+        ).await.into_iter().collect::<Result<Vec<_>, _>>();
+
+        // report why the announcement failed before anything that went wrong restoring the group
+        announced.and(restored).and(volumes_restored.map(|_| ()))
+    }
+}
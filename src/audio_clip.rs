@@ -0,0 +1,70 @@
+use crate::device::{xml_escape, Speaker};
+use crate::error::*;
+use crate::model::SonosFeature;
+use crate::service::Service;
+use crate::soap::soap_response;
+
+use failure::Error;
+
+/// Identifies this library to the speaker as the app that loaded a clip.
+const APP_ID: &str = "com.github.w4.sonos";
+
+soap_response! {
+    struct LoadAudioClipResponse {
+        id: String = "Id",
+    }
+}
+
+impl Speaker {
+    /// Play a short clip (eg. a doorbell chime) over the top of whatever the speaker is playing,
+    /// which carries on once the clip finishes. `volume` is from 0-100, or `None` to play the clip
+    /// at the speaker's current volume.
+    ///
+    /// Returns the ID the speaker gave the clip, which is needed to stop it early with
+    /// [Speaker::cancel_audio_clip].
+    ///
+    /// Unlike [Speaker::announce_to_group] this doesn't touch the speaker's transport, but it's only
+    /// available on S2 firmware so [SonosError::NotSupportedByDevice] is returned on S1.
+    pub async fn play_audio_clip(&self, uri: &str, volume: Option<u8>) -> Result<String, Error> {
+        if !self.supports_feature(SonosFeature::AudioClip) {
+            return Err(SonosError::NotSupportedByDevice(SonosFeature::AudioClip).into());
+        }
+
+        let volume = match volume {
+            Some(volume) if volume > 100 => panic!("Volume must be between 0 and 100, got {}.", volume),
+            Some(volume) => format!("<Volume>{}</Volume>", volume),
+            None => String::new(),
+        };
+
+        let resp: LoadAudioClipResponse = self.soap_typed(
+            &Service::AUDIO_CLIP,
+            "LoadAudioClip",
+            &format!(
+                "<AppId>{}</AppId><ClipType>CUSTOM</ClipType><StreamUrl>{}</StreamUrl><Priority>LOW</Priority>{}",
+                APP_ID,
+                xml_escape(uri),
+                volume
+            ),
+            false,
+        ).await?;
+
+        Ok(resp.id)
+    }
+
+    /// Stop the clip with the given ID, as returned by [Speaker::play_audio_clip], before it
+    /// finishes.
+    pub async fn cancel_audio_clip(&self, id: &str) -> Result<(), Error> {
+        if !self.supports_feature(SonosFeature::AudioClip) {
+            return Err(SonosError::NotSupportedByDevice(SonosFeature::AudioClip).into());
+        }
+
+        self.soap(
+            &Service::AUDIO_CLIP,
+            "CancelAudioClip",
+            &format!("<Id>{}</Id>", xml_escape(id)),
+            false,
+        ).await?;
+
+        Ok(())
+    }
+}
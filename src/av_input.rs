@@ -0,0 +1,96 @@
+use crate::device::{get_child_text, Speaker};
+use crate::error::*;
+use crate::service::Service;
+
+use failure::Error;
+use xmltree::Element;
+
+/// The kind of input a speaker is playing from, see [Speaker::get_input_av_source].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AVSourceType {
+    /// The HDMI ARC/eARC input of a home theatre speaker or Amp
+    Hdmi,
+    /// The optical input of a home theatre speaker
+    Optical,
+    /// An analogue line-in (RCA or 3.5mm depending on the model)
+    LineIn,
+    /// Anything that isn't a physical input, such as the queue or a radio station
+    Stream,
+}
+
+/// The input a speaker is playing from, as returned by [Speaker::get_input_av_source].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AVSource {
+    pub source_type: AVSourceType,
+    /// Title of what's playing as shown in the Sonos app (eg. the name of the speaker whose
+    /// line-in is used), or the URI being played if there's no title
+    pub description: String,
+}
+
+/// Get the dc:title out of a DIDL-Lite document describing a single item.
+fn didl_title(metadata: &str) -> Option<String> {
+    let didl = Element::parse(metadata.as_bytes()).ok()?;
+    let item = didl.get_child("item").or_else(|| didl.get_child("container"))?;
+
+    get_child_text(item, "title").filter(|title| !title.is_empty())
+}
+
+impl Speaker {
+    /// Get which input the speaker (or rather its group) is playing from.
+    ///
+    /// Sonos plays both HDMI and optical through the same TV stream, so which of those it is
+    /// is worked out from the speaker's model.
+    pub async fn get_input_av_source(&self) -> Result<AVSource, Error> {
+        let media = self.soap(
+            &Service::AV_TRANSPORT,
+            "GetMediaInfo",
+            "<InstanceID>0</InstanceID>",
+            true,
+        ).await?;
+
+        let uri = get_child_text(&media, "CurrentURI").unwrap_or_default();
+        let source_type = if uri.starts_with("x-sonos-htastream:") {
+            if self.has_optical_tv_input() {
+                AVSourceType::Optical
+            } else {
+                AVSourceType::Hdmi
+            }
+        } else if uri.starts_with("x-rincon-stream:") {
+            AVSourceType::LineIn
+        } else {
+            AVSourceType::Stream
+        };
+
+        let description = get_child_text(&media, "CurrentURIMetaData")
+            .and_then(|metadata| didl_title(&metadata))
+            .unwrap_or(uri);
+
+        Ok(AVSource { source_type, description })
+    }
+
+    /// Whether the speaker has the given kind of input, based on its model.
+    fn has_input(&self, source_type: AVSourceType) -> bool {
+        match source_type {
+            AVSourceType::Hdmi => self.supports_tv() && !self.has_optical_tv_input(),
+            AVSourceType::Optical => self.supports_tv() && self.has_optical_tv_input(),
+            AVSourceType::LineIn => self.supports_line_in(),
+            AVSourceType::Stream => false,
+        }
+    }
+
+    /// Switch the speaker to playing from one of its physical inputs.
+    ///
+    /// Returns [SonosError::UnsupportedInput] without calling the speaker if its model doesn't
+    /// have that kind of input, which is always the case for [AVSourceType::Stream].
+    pub async fn set_input_av_source(&self, source_type: AVSourceType) -> Result<(), Error> {
+        if !self.has_input(source_type) {
+            return Err(SonosError::UnsupportedInput(source_type).into());
+        }
+
+        match source_type {
+            AVSourceType::Hdmi | AVSourceType::Optical => self.play_tv().await,
+            AVSourceType::LineIn => self.play_line_in().await,
+            AVSourceType::Stream => unreachable!(),
+        }
+    }
+}
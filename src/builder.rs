@@ -0,0 +1,103 @@
+use crate::device::{description_endpoint, Speaker, SPEAKER_HTTP_CLIENT};
+use crate::discovery::discover_ips;
+use crate::error::*;
+use crate::service::SpeakerEndpoint;
+
+use std::net::IpAddr;
+use std::time::Duration;
+
+use failure::Error;
+use futures::future;
+
+/// Builds [Speaker]s with more control over how their device description is fetched than
+/// [Speaker::from_ip] gives.
+#[derive(Debug, Clone, Default)]
+pub struct SpeakerBuilder {
+    timeout: Option<Duration>,
+    client: Option<reqwest::Client>,
+    https: Option<bool>,
+    port: Option<u16>,
+}
+
+impl SpeakerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How long to wait for each speaker's device description before giving up with a
+    /// [SonosError::Timeout], no limit by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The client to fetch device descriptions with, rather than the one shared by every speaker.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Whether speakers should be talked to over HTTPS, rather than trying HTTPS and falling back
+    /// to HTTP.
+    pub fn https(mut self, https: bool) -> Self {
+        self.https = Some(https);
+        self
+    }
+
+    /// The port speakers' web servers are listening on, rather than the standard 1400 for HTTP or
+    /// 1443 for HTTPS. Speakers are talked to over HTTP on this port unless [SpeakerBuilder::https]
+    /// says otherwise.
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Build the speaker at `ip`.
+    pub async fn build(&self, ip: IpAddr) -> Result<Speaker, Error> {
+        let client = self.client.as_ref().unwrap_or(&SPEAKER_HTTP_CLIENT);
+        let speaker = async {
+            match (self.https, self.port) {
+                (https, Some(port)) => {
+                    let endpoint = SpeakerEndpoint {
+                        port,
+                        ..description_endpoint(ip, https.unwrap_or(false))
+                    };
+
+                    Speaker::from_description(endpoint, client).await
+                }
+                (Some(https), None) => Speaker::from_description(description_endpoint(ip, https), client).await,
+                (None, None) => Speaker::probe(ip, client).await,
+            }
+        };
+
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, speaker)
+                .await
+                .map_err(|_| SonosError::Timeout)?,
+            None => speaker.await,
+        }
+    }
+
+    /// Discover all speakers on the current network and build each of them.
+    ///
+    /// As with [crate::discover], devices that can't play audio on their own are left out and this
+    /// **will** block for 2 seconds while waiting for broadcast responses.
+    pub async fn discover_and_build(&self) -> Result<Vec<Speaker>, Error> {
+        let speakers = future::try_join_all(
+            discover_ips().await?.into_iter().map(|ip| self.build(ip)),
+        ).await?;
+
+        Ok(speakers
+            .into_iter()
+            .filter(|speaker| {
+                let playback = speaker.is_playback_device();
+
+                if !playback {
+                    debug!("Ignoring {} ({}) as it can't play audio", speaker.ip, speaker.model_number);
+                }
+
+                playback
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,69 @@
+use crate::device::{get_child_element_text, Speaker};
+use crate::service::Service;
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use failure::Error;
+
+/// How the time is shown on speakers with a clock display.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClockFormat {
+    TwelveHour,
+    TwentyFourHour,
+}
+
+impl ClockFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            ClockFormat::TwelveHour => "12H",
+            ClockFormat::TwentyFourHour => "24H",
+        }
+    }
+}
+
+impl Speaker {
+    /// Get the current time on the speaker's clock.
+    ///
+    /// Speakers sync their clocks over NTP, this is mostly useful for checking they've done so.
+    #[cfg(feature = "chrono")]
+    pub async fn clock_time(&self) -> Result<DateTime<Local>, Error> {
+        let resp = self.soap(
+            &Service::ALARM_CLOCK,
+            "GetTimeNow",
+            "",
+            false,
+        ).await?;
+
+        let utc = NaiveDateTime::parse_from_str(
+            &get_child_element_text(&resp, "CurrentUTCTime")?,
+            "%Y-%m-%d %H:%M:%S",
+        )?;
+
+        Ok(Utc.from_utc_datetime(&utc).with_timezone(&Local))
+    }
+
+    /// Set whether speakers with a clock display show the time in 12 or 24 hour format.
+    ///
+    /// The date format is left as it is.
+    pub async fn set_clock_format(&self, format: ClockFormat) -> Result<(), Error> {
+        let current = self.soap(
+            &Service::ALARM_CLOCK,
+            "GetFormat",
+            "",
+            false,
+        ).await?;
+
+        self.soap(
+            &Service::ALARM_CLOCK,
+            "SetFormat",
+            &format!(
+                "<DesiredTimeFormat>{}</DesiredTimeFormat><DesiredDateFormat>{}</DesiredDateFormat>",
+                format.as_str(),
+                get_child_element_text(&current, "CurrentDateFormat")?
+            ),
+            false,
+        ).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use crate::device::{get_child_element, get_child_text, Speaker};
+use crate::error::*;
+
+use failure::Error;
+use xmltree::{Element, XMLNode};
+
+/// A state variable of a service, as described by its Service Control Point Description.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateVariable {
+    pub name: String,
+    /// UPnP data type of the variable (eg. string, ui4 or boolean)
+    pub data_type: String,
+    /// Values the variable is limited to, empty if it isn't limited to a list of values
+    pub allowed_values: Vec<String>,
+    /// Lowest value the variable can take, if it's limited to a range
+    pub minimum: Option<String>,
+    /// Highest value the variable can take, if it's limited to a range
+    pub maximum: Option<String>,
+    pub default_value: Option<String>,
+}
+
+fn parse_state_variable(el: &Element) -> StateVariable {
+    StateVariable {
+        name: get_child_text(el, "name").unwrap_or_default(),
+        data_type: get_child_text(el, "dataType").unwrap_or_default(),
+        allowed_values: el
+            .get_child("allowedValueList")
+            .map(|list| {
+                list.children
+                    .iter()
+                    .filter_map(XMLNode::as_element)
+                    .filter_map(|value| value.get_text().map(|text| text.into_owned()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        minimum: el.get_child("allowedValueRange").and_then(|range| get_child_text(range, "minimum")),
+        maximum: el.get_child("allowedValueRange").and_then(|range| get_child_text(range, "maximum")),
+        default_value: get_child_text(el, "defaultValue"),
+    }
+}
+
+/// Find the device element of the given type (eg. MediaRenderer) among `el` and the devices
+/// embedded in it.
+fn find_device_of_type(el: &Element, device_type: &str) -> Option<Element> {
+    let matches = get_child_text(el, "deviceType")
+        .is_some_and(|t| t.starts_with(&format!("urn:schemas-upnp-org:device:{}:", device_type)));
+
+    if matches {
+        return Some(el.clone());
+    }
+
+    el.get_child("deviceList")?
+        .children
+        .iter()
+        .filter_map(XMLNode::as_element)
+        .find_map(|device| find_device_of_type(device, device_type))
+}
+
+impl Speaker {
+    /// Get the speaker's device description as the XML document served by the speaker.
+    pub async fn raw_device_description(&self) -> Result<String, Error> {
+        Ok(String::from_utf8(self.http_get("xml/device_description.xml").await?.to_vec())?)
+    }
+
+    /// Get the MediaRenderer device element from the speaker's device description, which lists
+    /// every service it provides in its serviceList along with their versions.
+    pub async fn get_media_renderer_description(&self) -> Result<Element, Error> {
+        let root = Element::parse(self.raw_device_description().await?.as_bytes())?;
+
+        find_device_of_type(get_child_element(&root, "device")?, "MediaRenderer").ok_or_else(|| {
+            SonosError::ParseError("missing MediaRenderer device in device description".to_string()).into()
+        })
+    }
+
+    /// Get the name of every action a service on the speaker supports, read from its Service
+    /// Control Point Description.
+    ///
+    /// `service_url` is the path to the description as given in the service's SCPDURL in the
+    /// device description (eg. /xml/AVTransport1.xml).
+    pub async fn get_action_list(&self, service_url: &str) -> Result<Vec<String>, Error> {
+        let scpd = self.scpd(service_url).await?;
+
+        Ok(get_child_element(&scpd, "actionList")?
+            .children
+            .iter()
+            .filter_map(XMLNode::as_element)
+            .filter_map(|action| get_child_text(action, "name"))
+            .collect())
+    }
+
+    /// Get every state variable of a service on the speaker, read from its Service Control Point
+    /// Description.
+    ///
+    /// See [Speaker::get_action_list] for what `service_url` is.
+    pub async fn get_state_variable_list(&self, service_url: &str) -> Result<Vec<StateVariable>, Error> {
+        let scpd = self.scpd(service_url).await?;
+
+        Ok(get_child_element(&scpd, "serviceStateTable")?
+            .children
+            .iter()
+            .filter_map(XMLNode::as_element)
+            .map(parse_state_variable)
+            .collect())
+    }
+
+    /// Fetch the Service Control Point Description at `service_url`.
+    async fn scpd(&self, service_url: &str) -> Result<Element, Error> {
+        Ok(Element::parse(self.http_get(service_url).await?.as_ref())?)
+    }
+}
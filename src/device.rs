@@ -1,18 +1,27 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
 use std::net::IpAddr;
 use std::time::Duration;
 
 use xmltree::{Element, XMLNode};
 use reqwest::header::HeaderMap;
+use bytes::Bytes;
 use regex::Regex;
 
 use crate::error::*;
+use crate::service::{Service, SpeakerEndpoint, HTTPS_PORT};
+use crate::soap::{soap_response, SoapRequest, SoapResponse};
 use failure::Error;
 use std::borrow::Cow;
-use std::num::ParseIntError;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Speaker {
     pub ip: IpAddr,
+    /// Whether the speaker is talked to over HTTPS
+    pub https: bool,
+    /// Port the speaker's web server is listening on, 1400 for HTTP or 1443 for HTTPS
+    pub port: u16,
     pub model: String,
     pub model_number: String,
     pub software_version: String,
@@ -22,6 +31,13 @@ pub struct Speaker {
     pub uuid: String,
 }
 
+/// Summarises the speaker as its name, model and IP, eg. `Living Room (Sonos Era 100, 192.168.1.50)`.
+impl fmt::Display for Speaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}, {})", self.name, self.model, self.ip)
+    }
+}
+
 #[derive(Debug)]
 pub struct Track {
     pub title: String,
@@ -29,11 +45,13 @@ pub struct Track {
     pub album: Option<String>,
     pub queue_position: u64,
     pub uri: String,
+    /// URI of the track's album art, usually a path relative to the speaker
+    pub album_art_uri: Option<String>,
     pub duration: Duration,
     pub running_time: Duration,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TransportState {
     Stopped,
     Playing,
@@ -43,36 +61,282 @@ pub enum TransportState {
     Transitioning,
 }
 
+/// What happens to the queue when playing a container with [Speaker::av_transport_play_container].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueuePolicy {
+    /// Clear the queue before adding the container's tracks
+    Replace,
+    /// Add the container's tracks to the end of the queue
+    Append,
+    /// Add the container's tracks after the current track
+    InsertNext,
+}
+
+/// What a speaker is playing from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioSource {
+    /// The speaker's queue
+    Queue,
+    /// The TV input of a home theatre speaker
+    Tv,
+    /// The analogue line-in of the speaker, or of another speaker in the household
+    LineIn,
+    /// A radio station or other stream
+    Radio,
+    /// Anything else, along with the URI being played
+    Other(String),
+    /// Nothing is loaded
+    Nothing,
+}
+
+impl AudioSource {
+    /// Work out the source from the transport URI of a speaker.
+    fn from_uri(uri: &str) -> AudioSource {
+        const RADIO_SCHEMES: &[&str] = &["x-sonosapi-stream:", "x-sonosapi-radio:", "x-rincon-mp3radio:", "aac:"];
+
+        if uri.is_empty() {
+            AudioSource::Nothing
+        } else if uri.starts_with("x-rincon-queue:") {
+            AudioSource::Queue
+        } else if uri.starts_with("x-sonos-htastream:") {
+            AudioSource::Tv
+        } else if uri.starts_with("x-rincon-stream:") {
+            AudioSource::LineIn
+        } else if RADIO_SCHEMES.iter().any(|scheme| uri.starts_with(scheme)) {
+            AudioSource::Radio
+        } else {
+            AudioSource::Other(uri.to_string())
+        }
+    }
+}
+
+/// ID of TuneIn as a music service on Sonos.
+const TUNEIN_SERVICE_ID: u32 = 254;
+
+/// Get the stream URI and DIDL-Lite metadata Sonos needs to play a TuneIn station, given its ID
+/// (eg. 24940 or s24940).
+pub(crate) fn tunein_station(station_id: &str) -> (String, String) {
+    let station_id = station_id.trim_start_matches('s');
+
+    let uri = format!("x-sonosapi-stream:s{}?sid={}", station_id, TUNEIN_SERVICE_ID);
+    // SA_RINCON65031_ is the account TuneIn is played through on every speaker
+    let metadata = format!(
+        r#"<DIDL-Lite xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/" xmlns:r="urn:schemas-rinconnetworks-com:metadata-1-0/" xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/"><item id="F00092020s{}" parentID="L" restricted="true"><dc:title></dc:title><upnp:class>object.item.audioItem.audioBroadcast</upnp:class><desc id="cdudn" nameSpace="urn:schemas-rinconnetworks-com:metadata-1-0/">SA_RINCON65031_</desc></item></DIDL-Lite>"#,
+        xml_escape(station_id),
+    );
+
+    (uri, metadata)
+}
+
+/// The protocols a speaker supports, as reported by its ConnectionManager.
+///
+/// Each entry is a UPnP protocolInfo string (eg. http-get:*:audio/mpeg:*). As with any UPnP
+/// renderer, `sink` lists what the speaker can play while `source` lists what it can serve.
+#[derive(Debug, Clone)]
+pub struct ProtocolInfo {
+    pub source: Vec<String>,
+    pub sink: Vec<String>,
+}
+
+soap_response! {
+    /// Response to GetVolume and GetGroupVolume
+    struct GetVolumeResponse {
+        current_volume: u8 = "CurrentVolume",
+    }
+}
+
+soap_response! {
+    struct GetMuteResponse {
+        current_mute: bool = "CurrentMute",
+    }
+}
+
+soap_response! {
+    /// Response to a Browse, ignoring the items that were browsed
+    struct BrowseTotalResponse {
+        total_matches: u64 = "TotalMatches",
+    }
+}
+
+/// How long to wait for a speaker to respond over HTTPS before falling back to HTTP.
+const HTTPS_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long to wait for a speaker to respond to a SOAP call, unless the request says otherwise.
+pub(crate) const SOAP_TIMEOUT: Duration = Duration::from_secs(10);
+
 lazy_static! {
-    static ref COORDINATOR_REGEX: Regex = Regex::new(r"^https?://(.+?):1400/xml")
+    static ref COORDINATOR_REGEX: Regex = Regex::new(r"^https?://(.+?):\d+/xml")
         .expect("Failed to create regex");
+
+    /// Client shared between all speakers so connections to them can be pooled.
+    ///
+    /// Speakers serving HTTPS use self-signed certificates, so this doesn't verify certificates
+    /// and must only be used for requests to speakers. Use [HTTP_CLIENT] for anything else.
+    pub(crate) static ref SPEAKER_HTTP_CLIENT: reqwest::Client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .expect("Failed to create HTTP client");
+
+    /// Client for requests to anything other than a speaker (eg. music services), which verifies
+    /// certificates as usual.
+    pub(crate) static ref HTTP_CLIENT: reqwest::Client = reqwest::Client::new();
 }
 
-fn get_child_element<'a>(el: &'a Element, name: &str) -> Result<&'a Element, Error> {
+/// Turn an error making a request to a speaker into the [SonosError] describing it, where there
+/// is one. Every request to a speaker goes through this, so an offline speaker is reported the
+/// same way whatever was being called.
+pub(crate) fn request_error(error: reqwest::Error) -> Error {
+    if error.is_timeout() {
+        return SonosError::Timeout.into();
+    }
+
+    // the reason a connection failed is given by the io::Error somewhere down the chain
+    let mut source = std::error::Error::source(&error);
+
+    while let Some(err) = source {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            match io.kind() {
+                std::io::ErrorKind::ConnectionRefused => return SonosError::ConnectionRefused.into(),
+                std::io::ErrorKind::NetworkUnreachable | std::io::ErrorKind::HostUnreachable => {
+                    return SonosError::NetworkUnreachable.into()
+                }
+                _ => {}
+            }
+        }
+
+        source = err.source();
+    }
+
+    error.into()
+}
+
+pub(crate) fn get_child_element<'a>(el: &'a Element, name: &str) -> Result<&'a Element, Error> {
     el.get_child(name)
         .ok_or_else(|| SonosError::ParseError(format!("missing {} element", name)).into())
 }
 
-fn get_child_element_text<'a>(el: &'a Element, name: &str) -> Result<Cow<'a, str>, Error> {
+pub(crate) fn get_child_element_text<'a>(el: &'a Element, name: &str) -> Result<Cow<'a, str>, Error> {
    get_child_element(el, name)?
         .get_text()
         .ok_or_else(|| SonosError::ParseError(format!("no text on {} element", name)).into())
 }
 
+/// Get the text of an optional child element, eg. one of the fields of a DIDL-Lite item.
+pub(crate) fn get_child_text(el: &Element, name: &str) -> Option<String> {
+    el.get_child(name)
+        .and_then(Element::get_text)
+        .map(|text| text.into_owned())
+}
+
+/// Convert a hh:mm:ss duration from Sonos to a Duration, `name` is used to describe the value in
+/// the error returned for invalid input.
+pub(crate) fn parse_duration(duration: &str, name: &str) -> Result<Duration, Error> {
+    let mut parts = duration.splitn(3, ':').map(|s| s.parse::<u64>());
+    let mut next = || parts.next().ok_or_else(|| SonosError::ParseError(format!("invalid {}", name)));
+    let (hours, minutes, seconds) = (next()??, next()??, next()??);
+
+    Ok(Duration::from_secs((hours * 3600) + (minutes * 60) + seconds))
+}
+
+/// Convert a Duration to the hh:mm:ss format Sonos expects.
+pub(crate) fn format_duration(duration: &Duration) -> String {
+    const SECS_PER_MINUTE: u64 = 60;
+    const MINS_PER_HOUR: u64 = 60;
+    const SECS_PER_HOUR: u64 = 3600;
+
+    let seconds = duration.as_secs() % SECS_PER_MINUTE;
+    let minutes = (duration.as_secs() / SECS_PER_MINUTE) % MINS_PER_HOUR;
+    let hours = duration.as_secs() / SECS_PER_HOUR;
+
+    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+}
+
+/// Get the endpoint the device description of the speaker at `ip` is served at.
+pub(crate) fn description_endpoint(ip: IpAddr, https: bool) -> SpeakerEndpoint {
+    let endpoint = SpeakerEndpoint::new(ip, "xml/device_description.xml");
+
+    if https {
+        SpeakerEndpoint { https, port: HTTPS_PORT, ..endpoint }
+    } else {
+        endpoint
+    }
+}
+
+/// Escape text so it can be embedded in the XML body of a SOAP call.
+pub(crate) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+impl TryFrom<&str> for TransportState {
+    type Error = SonosError;
+
+    /// Convert a transport state as reported by Sonos (eg. PAUSED_PLAYBACK).
+    fn try_from(state: &str) -> Result<Self, Self::Error> {
+        match state {
+            // NO_MEDIA_PRESENT is the UPnP state for a transport with nothing loaded
+            "STOPPED" | "NO_MEDIA_PRESENT" => Ok(TransportState::Stopped),
+            "PLAYING" => Ok(TransportState::Playing),
+            "PAUSED_PLAYBACK" => Ok(TransportState::PausedPlayback),
+            "PAUSED_RECORDING" => Ok(TransportState::PausedRecording),
+            "RECORDING" => Ok(TransportState::Recording),
+            "TRANSITIONING" => Ok(TransportState::Transitioning),
+            _ => Err(SonosError::ParseError(format!("unknown transport state {}", state))),
+        }
+    }
+}
+
 impl Speaker {
     /// Create a new instance of this struct from an IP address
+    ///
+    /// The speaker is talked to over HTTPS if it responds on port 1443, falling back to plain HTTP
+    /// on port 1400 otherwise.
     pub async fn from_ip(ip: IpAddr) -> Result<Speaker, Error> {
-        let resp = reqwest::get(&format!("http://{}:1400/xml/device_description.xml", ip)).await?;
+        Self::probe(ip, &SPEAKER_HTTP_CLIENT).await
+    }
+
+    /// Fetch the device description of the speaker at `ip` using `client`, trying HTTPS before
+    /// falling back to HTTP.
+    pub(crate) async fn probe(ip: IpAddr, client: &reqwest::Client) -> Result<Speaker, Error> {
+        let https = description_endpoint(ip, true);
+
+        match tokio::time::timeout(HTTPS_PROBE_TIMEOUT, Self::from_description(https, client)).await {
+            Ok(Ok(speaker)) => Ok(speaker),
+            probe => {
+                if let Ok(Err(e)) = probe {
+                    debug!("{} didn't respond over HTTPS ({}), falling back to HTTP", ip, e);
+                }
+
+                Self::from_description(description_endpoint(ip, false), client).await
+            }
+        }
+    }
+
+    /// Create a new instance of this struct from the device description served at `endpoint`.
+    pub(crate) async fn from_description(
+        endpoint: SpeakerEndpoint,
+        client: &reqwest::Client,
+    ) -> Result<Speaker, Error> {
+        let resp = client
+            .get(endpoint.url())
+            .send()
+            .await
+            .map_err(request_error)?;
 
         if !resp.status().is_success() {
             return Err(SonosError::BadResponse(resp.status().as_u16()).into());
         }
 
-        let root = Element::parse(resp.bytes().await?.as_ref())?;
+        let root = Element::parse(resp.bytes().await.map_err(request_error)?.as_ref())?;
         let device_description = get_child_element(&root, "device")?;
 
         Ok(Speaker {
-            ip,
+            ip: endpoint.ip,
+            https: endpoint.https,
+            port: endpoint.port,
             model: get_child_element_text(device_description, "modelName")?.into_owned(),
             model_number: get_child_element_text(device_description, "modelNumber")?.into_owned(),
             software_version: get_child_element_text(device_description, "softwareVersion")?.into_owned(),
@@ -84,16 +348,48 @@ impl Speaker {
         })
     }
 
+    /// The firmware version of the speaker (eg. 56.0-76060).
+    pub fn firmware_version(&self) -> &str {
+        &self.software_version
+    }
+
+    /// Get the numeric component at `index` of the dot-separated firmware version, ignoring the
+    /// build number.
+    fn firmware_component(&self, index: usize) -> u32 {
+        self.software_version
+            .split('-')
+            .next()
+            .and_then(|version| version.split('.').nth(index))
+            .and_then(|component| component.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// The major firmware version of the speaker (eg. 56 for 56.0-76060), or 0 if the version
+    /// couldn't be parsed.
+    pub fn firmware_major(&self) -> u32 {
+        self.firmware_component(0)
+    }
+
+    /// The minor firmware version of the speaker (eg. 0 for 56.0-76060), or 0 if the version
+    /// couldn't be parsed.
+    pub fn firmware_minor(&self) -> u32 {
+        self.firmware_component(1)
+    }
+
     /// Get the coordinator for this speaker.
     #[deprecated(note = "Broken on Sonos 9.1")]
     pub async fn coordinator(&self) -> Result<IpAddr, Error> {
-        let resp = reqwest::get(&format!("http://{}:1400/status/topology", self.ip)).await?;
+        let resp = SPEAKER_HTTP_CLIENT
+            .get(self.url("status/topology"))
+            .send()
+            .await
+            .map_err(request_error)?;
 
         if !resp.status().is_success() {
             return Err(SonosError::BadResponse(resp.status().as_u16()).into());
         }
 
-        let content = resp.text().await?;
+        let content = resp.text().await.map_err(request_error)?;
 
         // parse the topology xml
         let elements = Element::parse(content.as_bytes())?;
@@ -110,18 +406,14 @@ impl Speaker {
         let group = &zone_players
             .children
             .iter()
-            .map(XMLNode::as_element)
-            .filter(Option::is_some)
-            .map(Option::unwrap)
+            .filter_map(XMLNode::as_element)
             .find(|child| child.attributes["uuid"] == self.uuid)
             .ok_or_else(|| SonosError::DeviceNotFound(self.uuid.to_string()))?
             .attributes["group"];
 
         let parent = zone_players.children.iter()
             // get the coordinator for the given group
-            .map(XMLNode::as_element)
-            .filter(Option::is_some)
-            .map(Option::unwrap)
+            .filter_map(XMLNode::as_element)
             .find(|child|
                 child.attributes.get("coordinator").unwrap_or(&"false".to_string()) == "true" &&
                     child.attributes.get("group").unwrap_or(&"".to_string()) == group)
@@ -136,6 +428,132 @@ impl Speaker {
             .parse()?)
     }
 
+    /// Get the endpoint for a path on this speaker's web server.
+    pub fn endpoint(&self, path: &str) -> SpeakerEndpoint {
+        SpeakerEndpoint {
+            https: self.https,
+            port: self.port,
+            ..SpeakerEndpoint::new(self.ip, path)
+        }
+    }
+
+    /// Check if an absolute URL points at this speaker's web server rather than somewhere else
+    /// (eg. a music service's CDN).
+    fn is_own_url(&self, url: &str) -> bool {
+        reqwest::Url::parse(url).is_ok_and(|url| {
+            url.host_str().and_then(|host| host.trim_matches(&['[', ']'][..]).parse::<IpAddr>().ok()) == Some(self.ip)
+                && url.port_or_known_default() == Some(self.port)
+        })
+    }
+
+    /// Build the URL for a path on this speaker's web server.
+    pub(crate) fn url(&self, path: &str) -> String {
+        self.endpoint(path).url()
+    }
+
+    /// URL to subscribe to AVTransport events from this speaker at.
+    pub fn av_transport_event_url(&self) -> String {
+        self.url(Service::AV_TRANSPORT.event_path())
+    }
+
+    /// URL to subscribe to RenderingControl (volume and EQ) events from this speaker at.
+    pub fn rendering_control_event_url(&self) -> String {
+        self.url(Service::RENDERING_CONTROL.event_path())
+    }
+
+    /// URL to subscribe to queue events from this speaker at.
+    pub fn queue_event_url(&self) -> String {
+        self.url(Service::QUEUE.event_path())
+    }
+
+    /// URL to subscribe to ContentDirectory events from this speaker at.
+    pub fn content_directory_event_url(&self) -> String {
+        self.url(Service::CONTENT_DIRECTORY.event_path())
+    }
+
+    /// URL to subscribe to ZoneGroupTopology events from this speaker at.
+    pub fn zone_group_topology_event_url(&self) -> String {
+        self.url(Service::ZONE_GROUP_TOPOLOGY.event_path())
+    }
+
+    /// URL to subscribe to AlarmClock events from this speaker at.
+    pub fn alarm_clock_event_url(&self) -> String {
+        self.url(Service::ALARM_CLOCK.event_path())
+    }
+
+    /// URL to subscribe to SystemProperties events from this speaker at.
+    pub fn system_properties_event_url(&self) -> String {
+        self.url(Service::SYSTEM_PROPERTIES.event_path())
+    }
+
+    /// Make a plain HTTP GET request against this speaker and return the response body.
+    ///
+    /// `path` is relative to the speaker's web server (eg. /status/perf or
+    /// /xml/device_description.xml).
+    pub async fn http_get(&self, path: &str) -> Result<Bytes, Error> {
+        let resp = SPEAKER_HTTP_CLIENT
+            .get(self.url(path))
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        if !resp.status().is_success() {
+            return Err(SonosError::BadResponse(resp.status().as_u16()).into());
+        }
+
+        resp.bytes().await.map_err(request_error)
+    }
+
+    /// Fetch the JSON status document served by this speaker.
+    ///
+    /// This is read from the /info endpoint which is only served by S2 firmware, older speakers
+    /// will return a [SonosError::BadResponse].
+    #[cfg(feature = "serde_json")]
+    pub async fn status_json(&self) -> Result<serde_json::Value, Error> {
+        Ok(serde_json::from_slice(&self.http_get("/info").await?)?)
+    }
+
+    /// Call an action on one of the speaker's SOAP services
+    ///
+    /// # Arguments
+    /// * `service` - The service to call the action on (eg. [Service::AV_TRANSPORT])
+    /// * `action` - The action to call on the soap service (eg. Play)
+    /// * `payload` - XML doc to pass inside the action call body
+    /// * `coordinator` - Whether this SOAP call should be performed on the group coordinator or
+    ///   the speaker it was called on
+    pub(crate) async fn soap(
+        &self,
+        service: &Service,
+        action: &str,
+        payload: &str,
+        coordinator: bool,
+    ) -> Result<Element, Error> {
+        SoapRequest::for_service(service)
+            .action(action)
+            .payload(payload)
+            .use_coordinator(coordinator)
+            .send(self)
+            .await
+    }
+
+    /// Call an action on one of the speaker's SOAP services, reading the response into `T`.
+    ///
+    /// See [Speaker::soap] for the arguments.
+    pub(crate) async fn soap_typed<T: SoapResponse>(
+        &self,
+        service: &Service,
+        action: &str,
+        payload: &str,
+        coordinator: bool,
+    ) -> Result<T, Error> {
+        SoapRequest::for_service(service)
+            .action(action)
+            .payload(payload)
+            .use_coordinator(coordinator)
+            .send_typed(self)
+            .await
+    }
+
     /// Call the Sonos SOAP endpoint
     ///
     /// # Arguments
@@ -144,20 +562,43 @@ impl Speaker {
     /// * `action` - The action to call on the soap service (eg. Play)
     /// * `payload` - XML doc to pass inside the action call body
     /// * `coordinator` - Whether this SOAP call should be performed on the group coordinator or
-    ///                   the speaker it was called on
-    pub async fn soap(
+    ///   the speaker it was called on
+    /// * `timeout` - How long to wait for a response before giving up with a [SonosError::Timeout]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self, payload)))]
+    pub(crate) async fn call_soap(
         &self,
         endpoint: &str,
         service: &str,
         action: &str,
         payload: &str,
         coordinator: bool,
+        timeout: Duration,
+    ) -> Result<Element, Error> {
+        #[cfg(feature = "metrics")]
+        let start = std::time::Instant::now();
+
+        let result = self.send_soap(endpoint, service, action, payload, coordinator, timeout).await;
+
+        #[cfg(feature = "metrics")]
+        crate::instrumentation::record_soap_call(action, start.elapsed(), &result);
+
+        result
+    }
+
+    async fn send_soap(
+        &self,
+        endpoint: &str,
+        service: &str,
+        action: &str,
+        payload: &str,
+        coordinator: bool,
+        timeout: Duration,
     ) -> Result<Element, Error> {
         let mut headers = HeaderMap::new();
         headers.insert("Content-Type", "application/xml".parse()?);
         headers.insert("SOAPAction", format!("\"{}#{}\"", service, action).parse()?);
 
-        let client = reqwest::Client::new();
+        #[allow(deprecated)]
         let coordinator = if coordinator {
             self.coordinator().await?
         } else {
@@ -166,9 +607,11 @@ impl Speaker {
 
         debug!("Running {}#{} on {}", service, action, coordinator);
 
-        let request = client
-            .post(&format!("http://{}:1400/{}", coordinator, endpoint))
+        let request = SPEAKER_HTTP_CLIENT
+            // the coordinator is in the same household, so assume it's served the same way as us
+            .post(SpeakerEndpoint { ip: coordinator, ..self.endpoint(endpoint) }.url())
             .headers(headers)
+            .timeout(timeout)
             .body(format!(
                 r#"
             <s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/"
@@ -184,9 +627,10 @@ impl Speaker {
                 payload = payload
             ))
             .send()
-            .await?;
+            .await
+            .map_err(request_error)?;
 
-        let element = Element::parse(request.bytes().await?.as_ref())?;
+        let element = Element::parse(request.bytes().await.map_err(request_error)?.as_ref())?;
 
         let body = get_child_element(&element, "Body")?;
 
@@ -196,10 +640,9 @@ impl Speaker {
                 .and_then(|c| c.get_child("UPnPError"))
                 .and_then(|c| c.get_child("errorCode"))
                 .and_then(|c| c.get_text())
-                .ok_or_else(|| SonosError::ParseError("failed to parse error".to_string()))?
-                .parse::<u64>()?;
+                .ok_or_else(|| SonosError::ParseError("failed to parse error".to_string()))?;
 
-            let state = AVTransportError::from(error_code);
+            let state = AVTransportError::try_from(&*error_code)?;
             error!("Got state {:?} from {}#{} call.", state, service, action);
             Err(SonosError::from(state).into())
         } else {
@@ -207,11 +650,95 @@ impl Speaker {
         }
     }
 
+    /// Call any SOAP action on this speaker, for actions not yet wrapped by this library.
+    ///
+    /// The call is always made against this speaker rather than its group coordinator, and the
+    /// `{action}Response` element is returned as-is.
+    ///
+    /// # Arguments
+    /// * `endpoint` - The SOAP endpoint to call (eg. MediaRenderer/AVTransport/Control)
+    /// * `service` - The SOAP service to call (eg. urn:schemas-upnp-org:service:AVTransport:1)
+    /// * `action` - The action to call on the soap service (eg. Play)
+    /// * `payload` - XML doc to pass inside the action call body
+    pub async fn soap_any(
+        &self,
+        endpoint: &str,
+        service: &str,
+        action: &str,
+        payload: &str,
+    ) -> Result<Element, Error> {
+        SoapRequest::new()
+            .endpoint(endpoint)
+            .service(service)
+            .action(action)
+            .payload(payload)
+            .send(self)
+            .await
+    }
+
+    /// Call an action on the AVTransport service of this speaker's group coordinator, which is
+    /// where playback of the group is controlled.
+    pub async fn avt_action(&self, action: &str, payload: &str) -> Result<Element, Error> {
+        self.soap(&Service::AV_TRANSPORT, action, payload, true).await
+    }
+
+    /// Call an action on the RenderingControl service of this speaker.
+    pub async fn rc_action(&self, action: &str, payload: &str) -> Result<Element, Error> {
+        self.soap(&Service::RENDERING_CONTROL, action, payload, false).await
+    }
+
+    /// Call an action on the ContentDirectory service of this speaker.
+    pub async fn cd_action(&self, action: &str, payload: &str) -> Result<Element, Error> {
+        self.soap(&Service::CONTENT_DIRECTORY, action, payload, false).await
+    }
+
+    /// Read a single state variable (eg. TransportState) of one of the speaker's services using
+    /// the UPnP QueryStateVariable action.
+    ///
+    /// This is mostly useful for debugging, the actions returning the same information are much
+    /// more efficient.
+    pub async fn query_state_variable(&self, service: &Service, variable: &str) -> Result<String, Error> {
+        let resp = SoapRequest::new()
+            .endpoint(service.control_path())
+            .service("urn:schemas-upnp-org:control-1-0")
+            .action("QueryStateVariable")
+            .payload(&format!("<varName>{}</varName>", xml_escape(variable)))
+            .send(self)
+            .await?;
+
+        Ok(get_child_text(&resp, "return").unwrap_or_default())
+    }
+
+    /// Read the state variables of the AVTransport service, keyed by their name.
+    ///
+    /// See [Speaker::query_state_variable].
+    pub async fn get_av_transport_state_variables(&self) -> Result<HashMap<String, String>, Error> {
+        const VARIABLES: &[&str] = &[
+            "TransportState",
+            "TransportStatus",
+            "TransportPlaySpeed",
+            "CurrentPlayMode",
+            "NumberOfTracks",
+            "CurrentTrack",
+            "CurrentTrackDuration",
+            "CurrentTrackURI",
+            "AVTransportURI",
+            "RelativeTimePosition",
+        ];
+
+        let values = futures::future::try_join_all(
+            VARIABLES
+                .iter()
+                .map(|variable| self.query_state_variable(&Service::AV_TRANSPORT, variable)),
+        ).await?;
+
+        Ok(VARIABLES.iter().map(|variable| variable.to_string()).zip(values).collect())
+    }
+
     /// Play the current track
     pub async fn play(&self) -> Result<(), Error> {
         self.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+            &Service::AV_TRANSPORT,
             "Play",
             "<InstanceID>0</InstanceID><Speed>1</Speed>",
             true,
@@ -220,11 +747,26 @@ impl Speaker {
         Ok(())
     }
 
+    /// Play the current track at the given speed (eg. 2 for double speed or 0.5 for half speed)
+    ///
+    /// Most sources on Sonos only play at normal speed and will return
+    /// [AVTransportError::PlaySpeedNotSupported] for anything else, though some AV sources accept
+    /// other speeds.
+    pub async fn set_av_transport_speed(&self, speed: f32) -> Result<(), Error> {
+        self.soap(
+            &Service::AV_TRANSPORT,
+            "Play",
+            &format!("<InstanceID>0</InstanceID><Speed>{}</Speed>", speed),
+            true,
+        ).await?;
+
+        Ok(())
+    }
+
     /// Pause the current track
     pub async fn pause(&self) -> Result<(), Error> {
         self.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+            &Service::AV_TRANSPORT,
             "Pause",
             "<InstanceID>0</InstanceID>",
             true,
@@ -236,8 +778,7 @@ impl Speaker {
     /// Stop the current queue
     pub async fn stop(&self) -> Result<(), Error> {
         self.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+            &Service::AV_TRANSPORT,
             "Stop",
             "<InstanceID>0</InstanceID>",
             true,
@@ -248,21 +789,12 @@ impl Speaker {
 
     /// Seek to a time on the current track
     pub async fn seek(&self, time: &Duration) -> Result<(), Error> {
-        const SECS_PER_MINUTE: u64 = 60;
-        const MINS_PER_HOUR: u64 = 60;
-        const SECS_PER_HOUR: u64 = 3600;
-
-        let seconds = time.as_secs() % SECS_PER_MINUTE;
-        let minutes = (time.as_secs() / SECS_PER_MINUTE) % MINS_PER_HOUR;
-        let hours = time.as_secs() / SECS_PER_HOUR;
-
         self.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+            &Service::AV_TRANSPORT,
             "Seek",
             &format!(
-                "<InstanceID>0</InstanceID><Unit>REL_TIME</Unit><Target>{:02}:{:02}:{:02}</Target>",
-                hours, minutes, seconds
+                "<InstanceID>0</InstanceID><Unit>REL_TIME</Unit><Target>{}</Target>",
+                format_duration(time)
             ),
             true,
         ).await?;
@@ -280,18 +812,82 @@ impl Speaker {
         self.play_track(&format!("x-sonos-htastream:{}:spdif", self.uuid)).await
     }
 
+    /// Add a track to the queue after the current one and skip straight to it, leaving the rest
+    /// of the queue as it was.
+    pub async fn play_uri_next_in_queue(&self, uri: &str) -> Result<(), Error> {
+        let queue = self.queue();
+        let position = queue.enqueue(uri, "", true).await?;
+
+        queue.skip_to(&position).await
+    }
+
+    /// Add every track in a ContentDirectory container (eg. an album, artist or Sonos playlist)
+    /// to the queue as `queue_policy` says and start playing from the first of them.
+    ///
+    /// `container_uri` can either be the URI of the container as given in its `res` (eg.
+    /// x-rincon-playlist:RINCON_000E58000000001400#A:ALBUM/Album) or its ObjectID (eg.
+    /// A:ALBUM/Album or SQ:12), in which case the URI is looked up from the ContentDirectory.
+    pub async fn av_transport_play_container(
+        &self,
+        container_uri: &str,
+        queue_policy: QueuePolicy,
+    ) -> Result<(), Error> {
+        // every URI Sonos can enqueue has an x- or file: scheme, unlike ObjectIDs (eg. A:ALBUM)
+        let uri = if container_uri.starts_with("x-") || container_uri.starts_with("file:") {
+            container_uri.to_string()
+        } else {
+            let container = self.browse_metadata(container_uri).await?;
+            get_child_text(&container, "res")
+                .ok_or_else(|| SonosError::InvalidObjectId(container_uri.to_string()))?
+        };
+
+        let queue = self.queue();
+
+        if queue_policy == QueuePolicy::Replace {
+            queue.clear().await?;
+        }
+
+        let position = queue.enqueue(&uri, "", queue_policy == QueuePolicy::InsertNext).await?;
+
+        self.play_from_queue_at(position).await
+    }
+
+    /// Start playing the queue from the track at the given position, beginning at 1.
+    pub async fn play_from_queue_at(&self, position: u64) -> Result<(), Error> {
+        self.play_track(&format!("x-rincon-queue:{}#0", self.uuid)).await?;
+
+        self.soap(
+            &Service::AV_TRANSPORT,
+            "Seek",
+            &format!(
+                "<InstanceID>0</InstanceID><Unit>TRACK_NR</Unit><Target>{}</Target>",
+                position
+            ),
+            true,
+        ).await?;
+
+        self.play().await
+    }
+
     /// Replace the current track with a new one
     pub async fn play_track(&self, uri: &str) -> Result<(), Error> {
+        self.play_track_with_metadata(uri, "").await
+    }
+
+    /// Replace the current track with a new one, along with the DIDL-Lite document describing it.
+    ///
+    /// Some sources, such as music services, won't play without their metadata.
+    pub async fn play_track_with_metadata(&self, uri: &str, metadata: &str) -> Result<(), Error> {
         self.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+            &Service::AV_TRANSPORT,
             "SetAVTransportURI",
             &format!(
                 r#"
                   <InstanceID>0</InstanceID>
                   <CurrentURI>{}</CurrentURI>
-                  <CurrentURIMetaData></CurrentURIMetaData>"#,
-                uri
+                  <CurrentURIMetaData>{}</CurrentURIMetaData>"#,
+                uri,
+                xml_escape(metadata)
             ),
             true,
         ).await?;
@@ -299,17 +895,89 @@ impl Speaker {
         Ok(())
     }
 
+    /// Play a TuneIn radio station by its ID (eg. 24940 or s24940).
+    pub async fn play_tunein_station(&self, station_id: &str) -> Result<(), Error> {
+        let (uri, metadata) = tunein_station(station_id);
+
+        self.play_track_with_metadata(&uri, &metadata).await
+    }
+
+    /// Replace the current track with a new one and wait until it has finished playing.
+    ///
+    /// This is useful for playing short sounds such as doorbell chimes. [SonosError::Timeout] is
+    /// returned if the track hasn't finished playing within `timeout`.
+    pub async fn play_uri_and_wait(&self, uri: &str, timeout: Duration) -> Result<(), Error> {
+        self.play_track(uri).await?;
+        self.play().await?;
+
+        tokio::time::timeout(timeout, async {
+            // the speaker may still report the previous state for a moment after we hit play,
+            // so wait until we've seen it play before waiting for it to stop
+            self.poll_transport_state(|s| {
+                matches!(s, TransportState::Playing | TransportState::Transitioning)
+            }).await?;
+            self.poll_transport_state(|s| {
+                matches!(s, TransportState::Stopped | TransportState::PausedPlayback)
+            }).await
+        }).await.map_err(|_| SonosError::Timeout)?
+    }
+
+    /// Wait until this Speaker reaches the given transport state, returning
+    /// [SonosError::Timeout] if it doesn't within `timeout`.
+    ///
+    /// The state is polled, backing off from every 100ms up to every 2 seconds.
+    pub async fn wait_for_state(&self, state: TransportState, timeout: Duration) -> Result<(), Error> {
+        tokio::time::timeout(timeout, self.poll_transport_state(|s| s == state))
+            .await
+            .map_err(|_| SonosError::Timeout)?
+    }
+
+    /// Play the current track and wait until the speaker reports that it's playing.
+    pub async fn play_and_wait_for_playing(&self, timeout: Duration) -> Result<(), Error> {
+        self.play().await?;
+        self.wait_for_state(TransportState::Playing, timeout).await
+    }
+
+    /// Pause the current track and wait until the speaker reports that it's paused.
+    pub async fn pause_and_wait(&self, timeout: Duration) -> Result<(), Error> {
+        self.pause().await?;
+        self.wait_for_state(TransportState::PausedPlayback, timeout).await
+    }
+
+    /// Stop the current queue and wait until the speaker reports that it's stopped.
+    pub async fn stop_and_wait(&self, timeout: Duration) -> Result<(), Error> {
+        self.stop().await?;
+        self.wait_for_state(TransportState::Stopped, timeout).await
+    }
+
+    /// Poll the transport state until `done` returns true for it.
+    async fn poll_transport_state<F>(&self, done: F) -> Result<(), Error>
+    where
+        F: Fn(TransportState) -> bool,
+    {
+        const INITIAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+        const MAX_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+        let mut interval = INITIAL_POLL_INTERVAL;
+
+        while !done(self.transport_state().await?) {
+            tokio::time::sleep(interval).await;
+            interval = (interval * 2).min(MAX_POLL_INTERVAL);
+        }
+
+        Ok(())
+    }
+
     /// Get the current volume
     pub async fn volume(&self) -> Result<u8, Error> {
-        let res = self.soap(
-            "MediaRenderer/RenderingControl/Control",
-            "urn:schemas-upnp-org:service:RenderingControl:1",
+        let res: GetVolumeResponse = self.soap_typed(
+            &Service::RENDERING_CONTROL,
             "GetVolume",
             "<InstanceID>0</InstanceID><Channel>Master</Channel>",
             false,
         ).await?;
 
-        Ok(get_child_element_text(&res, "CurrentVolume")?.parse::<u8>()?)
+        Ok(res.current_volume)
     }
 
     /// Set a new volume from 0-100.
@@ -319,8 +987,7 @@ impl Speaker {
         }
 
         self.soap(
-            "MediaRenderer/RenderingControl/Control",
-            "urn:schemas-upnp-org:service:RenderingControl:1",
+            &Service::RENDERING_CONTROL,
             "SetVolume",
             &format!(
                 r#"
@@ -335,27 +1002,52 @@ impl Speaker {
         Ok(())
     }
 
+    /// Get the volume of the group this speaker is in from 0-100, which is the average volume of
+    /// its members.
+    pub async fn group_volume(&self) -> Result<u8, Error> {
+        let res: GetVolumeResponse = self.soap_typed(
+            &Service::GROUP_RENDERING_CONTROL,
+            "GetGroupVolume",
+            "<InstanceID>0</InstanceID>",
+            true,
+        ).await?;
+
+        Ok(res.current_volume)
+    }
+
+    /// Set the volume of the group this speaker is in from 0-100, keeping the volume of each
+    /// member relative to the others.
+    pub async fn set_group_volume(&self, volume: u8) -> Result<(), Error> {
+        if volume > 100 {
+            panic!("Volume must be between 0 and 100, got {}.", volume);
+        }
+
+        self.soap(
+            &Service::GROUP_RENDERING_CONTROL,
+            "SetGroupVolume",
+            &format!("<InstanceID>0</InstanceID><DesiredVolume>{}</DesiredVolume>", volume),
+            true,
+        ).await?;
+
+        Ok(())
+    }
+
     /// Check if this player is currently muted
     pub async fn muted(&self) -> Result<bool, Error> {
-        let resp = self.soap(
-            "MediaRenderer/RenderingControl/Control",
-            "urn:schemas-upnp-org:service:RenderingControl:1",
+        let resp: GetMuteResponse = self.soap_typed(
+            &Service::RENDERING_CONTROL,
             "GetMute",
             "<InstanceID>0</InstanceID><Channel>Master</Channel>",
             false,
         ).await?;
 
-        Ok(match get_child_element_text(&resp, "CurrentMute")?.as_ref() {
-            "1" => true,
-            "0" | _ => false,
-        })
+        Ok(resp.current_mute)
     }
 
     /// Mute this Speaker
     pub async fn mute(&self) -> Result<(), Error> {
         self.soap(
-            "MediaRenderer/RenderingControl/Control",
-            "urn:schemas-upnp-org:service:RenderingControl:1",
+            &Service::RENDERING_CONTROL,
             "SetMute",
             "<InstanceID>0</InstanceID><Channel>Master</Channel><DesiredMute>1</DesiredMute>",
             false,
@@ -367,8 +1059,7 @@ impl Speaker {
     /// Unmute this Speaker
     pub async fn unmute(&self) -> Result<(), Error> {
         self.soap(
-            "MediaRenderer/RenderingControl/Control",
-            "urn:schemas-upnp-org:service:RenderingControl:1",
+            &Service::RENDERING_CONTROL,
             "SetMute",
             "<InstanceID>0</InstanceID><Channel>Master</Channel><DesiredMute>0</DesiredMute>",
             false,
@@ -380,20 +1071,41 @@ impl Speaker {
     /// Get the transport state of this Speaker
     pub async fn transport_state(&self) -> Result<TransportState, Error> {
         let resp = self.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+            &Service::AV_TRANSPORT,
             "GetTransportInfo",
             "<InstanceID>0</InstanceID>",
             false,
         ).await?;
 
-        Ok(match get_child_element_text(&resp, "CurrentTransportState")?.as_ref() {
-            "PLAYING" => TransportState::Playing,
-            "PAUSED_PLAYBACK" => TransportState::PausedPlayback,
-            "PAUSED_RECORDING" => TransportState::PausedRecording,
-            "RECORDING" => TransportState::Recording,
-            "TRANSITIONING" => TransportState::Transitioning,
-            "STOPPED" | _ => TransportState::Stopped,
+        Ok(TransportState::try_from(&*get_child_element_text(&resp, "CurrentTransportState")?)?)
+    }
+
+    /// Get the protocols this Speaker can play (sink) and serve (source).
+    pub async fn get_protocol_info(&self) -> Result<ProtocolInfo, Error> {
+        let resp = self.soap(
+            &Service::CONNECTION_MANAGER,
+            "GetProtocolInfo",
+            "",
+            false,
+        ).await?;
+
+        // an empty list is returned as an element without any text
+        let parse_list = |name| -> Result<Vec<String>, Error> {
+            Ok(get_child_element(&resp, name)?
+                .get_text()
+                .map(|list| {
+                    list.split(',')
+                        .map(str::trim)
+                        .filter(|p| !p.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default())
+        };
+
+        Ok(ProtocolInfo {
+            source: parse_list("Source")?,
+            sink: parse_list("Sink")?,
         })
     }
 
@@ -407,8 +1119,7 @@ impl Speaker {
     /// Ungroups this Speaker from any master it might've had.
     pub async fn ungroup(&self) -> Result<(), Error> {
         self.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+            &Service::AV_TRANSPORT,
             "BecomeCoordinatorOfStandaloneGroup",
             "<InstanceID>0</InstanceID>",
             true,
@@ -418,19 +1129,31 @@ impl Speaker {
     }
 
     /// Grab this Speaker's queue to manipulate.
-    pub fn queue(&self) -> Queue {
+    pub fn queue(&self) -> Queue<'_> {
         Queue::for_speaker(self)
     }
 
-    /// Get information about what's currently playing on this Speaker.
-    pub async fn track(&self) -> Result<Track, Error> {
-        let resp = self.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+    /// Get the full GetPositionInfo response for this speaker's group, for fields not exposed
+    /// elsewhere (eg. AbsTime or RelCount).
+    pub async fn get_position_info_raw(&self) -> Result<Element, Error> {
+        self.soap(
+            &Service::AV_TRANSPORT,
             "GetPositionInfo",
             "<InstanceID>0</InstanceID>",
             true,
-        ).await?;
+        ).await
+    }
+
+    /// Get how far into the current track playback is.
+    pub async fn position(&self) -> Result<Duration, Error> {
+        let resp = self.get_position_info_raw().await?;
+
+        parse_duration(&get_child_element_text(&resp, "RelTime")?, "RelTime")
+    }
+
+    /// Get information about what's currently playing on this Speaker.
+    pub async fn track(&self) -> Result<Track, Error> {
+        let resp = self.get_position_info_raw().await?;
 
         let metadata = get_child_element_text(&resp, "TrackMetaData")?;
 
@@ -442,41 +1165,105 @@ impl Speaker {
 
         let metadata = get_child_element(&metadata, "item")?;
 
-        // convert the given hh:mm:ss to a Duration
-        let mut duration = get_child_element_text(&resp, "TrackDuration")?
-            .splitn(3, ':')
-            .map(|s| s.parse::<u64>())
-            .collect::<Vec<Result<u64, ParseIntError>>>()
-            .into_iter();
-        let duration = (
-            duration.next().ok_or_else(|| SonosError::ParseError("invalid TrackDuration".to_string()))?,
-            duration.next().ok_or_else(|| SonosError::ParseError("invalid TrackDuration".to_string()))?,
-            duration.next().ok_or_else(|| SonosError::ParseError("invalid TrackDuration".to_string()))?,
-        );
-        let duration = Duration::from_secs((duration.0? * 3600) + (duration.1? * 60) + duration.2?);
-
-        let mut running_time = get_child_element_text(&resp, "RelTime")?
-            .splitn(3, ':')
-            .map(|s| s.parse::<u64>())
-            .collect::<Vec<Result<u64, ParseIntError>>>()
-            .into_iter();
-        let running_time = (
-            running_time.next().ok_or_else(|| SonosError::ParseError("invalid RelTime".to_string()))?,
-            running_time.next().ok_or_else(|| SonosError::ParseError("invalid RelTime".to_string()))?,
-            running_time.next().ok_or_else(|| SonosError::ParseError("invalid RelTime".to_string()))?,
-        );
-        let running_time = Duration::from_secs((running_time.0? * 3600) + (running_time.1? * 60) + running_time.2?);
+        let duration = parse_duration(&get_child_element_text(&resp, "TrackDuration")?, "TrackDuration")?;
+        let running_time = parse_duration(&get_child_element_text(&resp, "RelTime")?, "RelTime")?;
 
         Ok(Track {
-            title: get_child_element_text(&metadata, "title")?.into_owned(),
-            artist: get_child_element_text(&metadata, "creator")?.into_owned(),
-            album: get_child_element_text(&metadata, "album").ok().map(Cow::into_owned),
+            title: get_child_element_text(metadata, "title")?.into_owned(),
+            artist: get_child_element_text(metadata, "creator")?.into_owned(),
+            album: get_child_element_text(metadata, "album").ok().map(Cow::into_owned),
             queue_position: get_child_element_text(&resp, "Track")?.parse::<u64>()?,
             uri: get_child_element_text(&resp, "TrackURI")?.into_owned(),
+            album_art_uri: get_child_text(metadata, "albumArtURI").filter(|uri| !uri.is_empty()),
             duration,
             running_time,
         })
     }
+
+    /// Fetch the album art of the track that's currently playing.
+    ///
+    /// Returns `None` if the track has no album art, or the album art couldn't be found.
+    pub async fn art_for_current_track(&self) -> Result<Option<Bytes>, Error> {
+        let uri = match self.track().await?.album_art_uri {
+            Some(uri) => uri,
+            None => return Ok(None),
+        };
+
+        // album art from music services can be an absolute URL rather than a path on the speaker
+        let (url, client) = if uri.starts_with("http://") || uri.starts_with("https://") {
+            let client = if self.is_own_url(&uri) { &*SPEAKER_HTTP_CLIENT } else { &*HTTP_CLIENT };
+            (uri, client)
+        } else {
+            (self.url(&uri), &*SPEAKER_HTTP_CLIENT)
+        };
+
+        let resp = client.get(&url).send().await?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !resp.status().is_success() {
+            return Err(SonosError::BadResponse(resp.status().as_u16()).into());
+        }
+
+        Ok(Some(resp.bytes().await?))
+    }
+
+    /// Get what the speaker (or rather its group) is playing from.
+    pub async fn source(&self) -> Result<AudioSource, Error> {
+        let media = self.soap(
+            &Service::AV_TRANSPORT,
+            "GetMediaInfo",
+            "<InstanceID>0</InstanceID>",
+            true,
+        ).await?;
+
+        Ok(AudioSource::from_uri(&get_child_text(&media, "CurrentURI").unwrap_or_default()))
+    }
+
+    /// Check if the speaker is playing from the TV input.
+    pub async fn is_playing_tv(&self) -> Result<bool, Error> {
+        Ok(self.source().await? == AudioSource::Tv)
+    }
+
+    /// Check if the speaker is playing from a line-in.
+    pub async fn is_playing_line_in(&self) -> Result<bool, Error> {
+        Ok(self.source().await? == AudioSource::LineIn)
+    }
+
+    /// Get the number of tracks in the queue without fetching all of them.
+    pub async fn get_queue_total(&self) -> Result<u64, Error> {
+        // a RequestedCount of 0 asks for every item, so ask for just one to get the total
+        let resp: BrowseTotalResponse = self.soap_typed(
+            &Service::CONTENT_DIRECTORY,
+            "Browse",
+            "<ObjectID>Q:0</ObjectID><BrowseFlag>BrowseDirectChildren</BrowseFlag>\
+             <Filter></Filter><StartingIndex>0</StartingIndex>\
+             <RequestedCount>1</RequestedCount><SortCriteria></SortCriteria>",
+            true,
+        ).await?;
+
+        Ok(resp.total_matches)
+    }
+
+    /// Remove the track that's currently playing from the queue.
+    ///
+    /// Returns [SonosError::NotPlayingFromQueue] if the speaker is playing something other than
+    /// its queue, such as a radio station or line-in.
+    pub async fn remove_current_track_from_queue(&self) -> Result<(), Error> {
+        if self.source().await? != AudioSource::Queue {
+            return Err(SonosError::NotPlayingFromQueue.into());
+        }
+
+        let position = self.get_position_info_raw().await?;
+
+        // the track number is 0 when the queue is empty
+        match get_child_element_text(&position, "Track")?.parse::<u64>()? {
+            0 => Err(SonosError::QueueItemNotFound(0).into()),
+            track => self.queue().remove(&track).await,
+        }
+    }
 }
 
 /// An item in the queue.
@@ -490,10 +1277,36 @@ pub struct QueueItem {
     pub duration: Duration,
 }
 
+/// Parse an item from the DIDL-Lite document returned when browsing the queue.
+fn parse_queue_item(el: &Element) -> Result<QueueItem, Error> {
+    let id = el.attributes.get("id").map(String::as_str).unwrap_or_default();
+    let position = id
+        .rsplit('/')
+        .next()
+        .and_then(|position| position.parse().ok())
+        .ok_or_else(|| SonosError::ParseError(format!("invalid queue item id {}", id)))?;
+
+    Ok(QueueItem {
+        position,
+        uri: get_child_text(el, "res").unwrap_or_default(),
+        title: get_child_text(el, "title").unwrap_or_default(),
+        artist: get_child_text(el, "creator").unwrap_or_default(),
+        album: get_child_text(el, "album").unwrap_or_default(),
+        album_art: get_child_text(el, "albumArtURI").unwrap_or_default(),
+        duration: parse_duration(
+            el.get_child("res")
+                .and_then(|res| res.attributes.get("duration"))
+                .map(String::as_str)
+                .unwrap_or_default(),
+            "duration",
+        )?,
+    })
+}
+
 /// Provides some methods for manipulating the queue of the
 /// [Speaker] that spawned this [Queue].
 pub struct Queue<'a> {
-    speaker: &'a Speaker,
+    pub(crate) speaker: &'a Speaker,
 }
 impl<'a> Queue<'a> {
     pub fn for_speaker(speaker: &'a Speaker) -> Self {
@@ -502,75 +1315,70 @@ impl<'a> Queue<'a> {
         }
     }
 
-    pub async fn list(&self) -> Result<Vec<QueueItem>, Error> {
+    /// Get `count` items from the queue starting at the zero-based index `start`.
+    async fn browse(&self, start: u64, count: u64) -> Result<Vec<QueueItem>, Error> {
         let res = self.speaker.soap(
-            "MediaServer/ContentDirectory/Control",
-            "urn:schemas-upnp-org:service:ContentDirectory:1",
+            &Service::CONTENT_DIRECTORY,
             "Browse",
-            r"
+            &format!(r"
                 <ObjectID>Q:0</ObjectID>
                 <BrowseFlag>BrowseDirectChildren</BrowseFlag>
                 <Filter></Filter>
-                <StartingIndex>0</StartingIndex>
-                <RequestedCount>1000</RequestedCount>
-                <SortCriteria></SortCriteria>",
+                <StartingIndex>{}</StartingIndex>
+                <RequestedCount>{}</RequestedCount>
+                <SortCriteria></SortCriteria>", start, count),
             true
         ).await?;
 
-        let results = Element::parse(
-            res.get_child("Result")
-                .and_then(Element::get_text)
-                .ok_or_else(|| SonosError::ParseError("missing Result element".to_string()))?
-                .as_bytes()
-        )?;
-
-        let mut tracks = Vec::new();
-
-        for child in results.children {
-            if let Some(child) = child.as_element() {
-                tracks.push(QueueItem {
-                    position: child.attributes.get("id").cloned().unwrap_or_default().split('/').next_back().unwrap().parse().unwrap(),
-                    uri: child.get_child("res")
-                        .and_then(Element::get_text)
-                        .map(|e| e.to_string())
-                        .unwrap_or_default(),
-                    title: child.get_child("title")
-                        .and_then(Element::get_text)
-                        .map(|e| e.to_string())
-                        .unwrap_or_default(),
-                    artist: child.get_child("creator")
-                        .and_then(Element::get_text)
-                        .map(|e| e.to_string())
-                        .unwrap_or_default(),
-                    album: child.get_child("album")
-                        .and_then(Element::get_text)
-                        .map(|e| e.to_string())
-                        .unwrap_or_default(),
-                    album_art: child.get_child("albumArtURI")
-                        .and_then(Element::get_text)
-                        .map(|e| e.to_string())
-                        .unwrap_or_default(),
-                    duration: {
-                        let mut duration = child.get_child("res")
-                            .map(|e| e.attributes.get("duration").cloned().unwrap_or_default())
-                            .unwrap()
-                            .splitn(3, ':')
-                            .map(|s| s.parse::<u64>())
-                            .collect::<Vec<Result<u64, std::num::ParseIntError>>>();
-                        Duration::from_secs((duration.remove(0)? * 3600) + (duration.remove(0)? * 60) + duration.remove(0)?)
-                    }
-                });
+        let results = Element::parse(get_child_element_text(&res, "Result")?.as_bytes())?;
+
+        results
+            .children
+            .iter()
+            .filter_map(XMLNode::as_element)
+            .map(parse_queue_item)
+            .collect()
+    }
+
+    pub async fn list(&self) -> Result<Vec<QueueItem>, Error> {
+        self.browse(0, 1000).await
+    }
+
+    /// Get every item in the queue, fetching it 1000 items at a time rather than stopping at
+    /// the first 1000 like [Queue::list].
+    pub async fn list_all(&self) -> Result<Vec<QueueItem>, Error> {
+        const PAGE_SIZE: u64 = 1000;
+
+        let mut items = Vec::new();
+
+        loop {
+            let page = self.browse(items.len() as u64, PAGE_SIZE).await?;
+            let done = (page.len() as u64) < PAGE_SIZE;
+            items.extend(page);
+
+            if done {
+                return Ok(items);
             }
         }
+    }
+
+    /// Get the item at the given position (starting from 1) in the queue, without fetching the
+    /// rest of the queue.
+    pub async fn item_at(&self, position: u64) -> Result<QueueItem, Error> {
+        if position == 0 {
+            return Err(SonosError::QueueItemNotFound(position).into());
+        }
 
-        Ok(tracks)
+        self.browse(position - 1, 1)
+            .await?
+            .pop()
+            .ok_or_else(|| SonosError::QueueItemNotFound(position).into())
     }
 
     /// Skip the current track
     pub async fn next(&self) -> Result<(), Error> {
         self.speaker.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+            &Service::AV_TRANSPORT,
             "Next",
             "<InstanceID>0</InstanceID>",
             true,
@@ -584,8 +1392,7 @@ impl<'a> Queue<'a> {
     /// Go to the previous track
     pub async fn previous(&self) -> Result<(), Error> {
         self.speaker.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+            &Service::AV_TRANSPORT,
             "Previous",
             "<InstanceID>0</InstanceID>",
             true,
@@ -598,29 +1405,13 @@ impl<'a> Queue<'a> {
 
     /// Change the track, beginning at 1
     pub async fn skip_to(&self, track: &u64) -> Result<(), Error> {
-        self.speaker.play_track(&format!("x-rincon-queue:{}#0", self.speaker.uuid)).await?;
-
-        self.speaker.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
-            "Seek",
-            &format!(
-                "<InstanceID>0</InstanceID><Unit>TRACK_NR</Unit><Target>{}</Target>",
-                track
-            ),
-            true,
-        ).await?;
-
-        self.speaker.play().await?;
-
-        Ok(())
+        self.speaker.play_from_queue_at(*track).await
     }
 
     /// Remove track at index from queue, beginning at 1
     pub async fn remove(&self, track: &u64) -> Result<(), Error> {
         self.speaker.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+            &Service::AV_TRANSPORT,
             "RemoveTrackFromQueue",
             &format!(
                 "<InstanceID>0</InstanceID><ObjectID>Q:0/{}</ObjectID>",
@@ -632,44 +1423,66 @@ impl<'a> Queue<'a> {
         Ok(())
     }
 
-    /// Add a new track to the end of the queue
-    pub async fn add_end(&self, uri: &str) -> Result<(), Error> {
-        self.speaker.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+    /// Remove every track with the same URI as one earlier in the queue, returning how many
+    /// were removed.
+    pub async fn deduplicate(&self) -> Result<u64, Error> {
+        let mut seen = HashSet::new();
+        let duplicates: Vec<u64> = self
+            .list_all()
+            .await?
+            .into_iter()
+            .filter(|item| !seen.insert(item.uri.clone()))
+            .map(|item| item.position)
+            .collect();
+
+        // removing from the back keeps the positions of the remaining duplicates the same
+        for position in duplicates.iter().rev() {
+            self.remove(position).await?;
+        }
+
+        Ok(duplicates.len() as u64)
+    }
+
+    /// Add a track to the queue, returning the position it was added at.
+    async fn enqueue(&self, uri: &str, metadata: &str, as_next: bool) -> Result<u64, Error> {
+        let resp = self.speaker.soap(
+            &Service::AV_TRANSPORT,
             "AddURIToQueue",
             &format!(
                 r#"
                   <InstanceID>0</InstanceID>
                   <EnqueuedURI>{}</EnqueuedURI>
-                  <EnqueuedURIMetaData></EnqueuedURIMetaData>
+                  <EnqueuedURIMetaData>{}</EnqueuedURIMetaData>
                   <DesiredFirstTrackNumberEnqueued>0</DesiredFirstTrackNumberEnqueued>
-                  <EnqueueAsNext>0</EnqueueAsNext>"#,
-                uri
+                  <EnqueueAsNext>{}</EnqueueAsNext>"#,
+                uri,
+                xml_escape(metadata),
+                as_next as u8
             ),
             true,
         ).await?;
 
+        Ok(get_child_element_text(&resp, "FirstTrackNumberEnqueued")?.parse()?)
+    }
+
+    /// Add a new track to the end of the queue
+    pub async fn add_end(&self, uri: &str) -> Result<(), Error> {
+        self.enqueue(uri, "", false).await?;
+
         Ok(())
     }
 
     /// Add a track to the queue to play next
     pub async fn add_next(&self, uri: &str) -> Result<(), Error> {
-        self.speaker.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
-            "AddURIToQueue",
-            &format!(
-                r#"
-                  <InstanceID>0</InstanceID>
-                  <EnqueuedURI>{}</EnqueuedURI>
-                  <EnqueuedURIMetaData></EnqueuedURIMetaData>
-                  <DesiredFirstTrackNumberEnqueued>0</DesiredFirstTrackNumberEnqueued>
-                  <EnqueueAsNext>1</EnqueueAsNext>"#,
-                uri
-            ),
-            true,
-        ).await?;
+        self.enqueue(uri, "", true).await?;
+
+        Ok(())
+    }
+
+    /// Add a TuneIn radio station to the end of the queue by its ID (eg. 24940 or s24940).
+    pub async fn add_tunein_station(&self, station_id: &str) -> Result<(), Error> {
+        let (uri, metadata) = tunein_station(station_id);
+        self.enqueue(&uri, &metadata, false).await?;
 
         Ok(())
     }
@@ -677,8 +1490,7 @@ impl<'a> Queue<'a> {
     /// Remove every track from the queue
     pub async fn clear(&self) -> Result<(), Error> {
         self.speaker.soap(
-            "MediaRenderer/AVTransport/Control",
-            "urn:schemas-upnp-org:service:AVTransport:1",
+            &Service::AV_TRANSPORT,
             "RemoveAllTracksFromQueue",
             "<InstanceID>0</InstanceID>",
             true,
@@ -1,5 +1,7 @@
 use crate::device::Speaker;
+use crate::error::*;
 
+use std::net::IpAddr;
 use std::time::Duration;
 use regex::Regex;
 
@@ -15,22 +17,66 @@ lazy_static! {
 
 /// Discover all speakers on the current network.
 ///
+/// Devices that can't play audio on their own (see [Speaker::is_playback_device]) are left out.
+///
 /// This method **will** block for 2 seconds while waiting for broadcast responses.
 pub async fn discover() -> Result<Vec<Speaker>, Error> {
+    let mut speakers = Vec::new();
+
+    for ip in discover_ips().await? {
+        let speaker = Speaker::from_ip(ip).await?;
+
+        if speaker.is_playback_device() {
+            speakers.push(speaker);
+        } else {
+            debug!("Ignoring {} ({}) as it can't play audio", speaker.ip, speaker.model_number);
+        }
+    }
+
+    Ok(speakers)
+}
+
+/// Get the IP of every device on the current network that responds to an SSDP search, waiting 2
+/// seconds for responses.
+pub(crate) async fn discover_ips() -> Result<Vec<IpAddr>, Error> {
     let search_target = URN::device("schemas-upnp-org", "ZonePlayer", 1).into();
     let timeout = Duration::from_secs(2);
     let responses = ssdp_client::search(&search_target, timeout, 1).await?;
     futures::pin_mut!(responses);
 
-    let mut speakers = Vec::new();
+    let mut ips = Vec::new();
 
     while let Some(response) = responses.next().await {
         let response = response?;
 
         if let Some(ip) = LOCATION_REGEX.captures(response.location()).and_then(|x| x.get(1)).map(|x| x.as_str()) {
-            speakers.push(Speaker::from_ip(ip.parse()?).await?);
+            ips.push(ip.parse()?);
         }
     }
 
-    Ok(speakers)
+    Ok(ips)
+}
+
+impl Speaker {
+    /// Find the speaker with the given UUID on the current network, for when its IP isn't known
+    /// or may have changed (eg. a UUID saved in configuration).
+    ///
+    /// The first speaker to respond to discovery is asked for the household's topology to find
+    /// the IP, so this **will** block for 2 seconds like [discover]. Keeping the IP of the
+    /// returned speaker around and trying [Speaker::from_ip] first saves the wait on later runs.
+    pub async fn from_uuid(uuid: &str) -> Result<Speaker, Error> {
+        let ip = discover_ips()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| SonosError::DeviceNotFound(uuid.to_string()))?;
+
+        let speaker = Speaker::from_ip(ip).await?;
+
+        if speaker.uuid == uuid {
+            return Ok(speaker);
+        }
+
+        speaker.query_topology_for_uuid(uuid).await?.to_speaker().await
+    }
 }
@@ -1,15 +1,39 @@
+use crate::av_input::AVSourceType;
+use crate::model::SonosFeature;
+
+use std::convert::TryFrom;
+use std::fmt;
+
 #[derive(Debug, Fail)]
 pub enum SonosError {
-    #[fail(display = "Received error {:?} from Sonos speaker", 0)]
-    AVTransportError(AVTransportError),
+    #[fail(display = "Received error {} from Sonos speaker", 0)]
+    AVTransportError(#[fail(cause)] AVTransportError),
     #[fail(display = "Failed to parse Sonos response XML ({})", 0)]
     ParseError(String),
     #[fail(display = "Failed to call Sonos endpoint")]
     DeviceUnreachable,
+    #[fail(display = "Timed out waiting for a response from Sonos")]
+    Timeout,
+    #[fail(display = "The Sonos speaker refused the connection")]
+    ConnectionRefused,
+    #[fail(display = "The network the Sonos speaker is on is unreachable")]
+    NetworkUnreachable,
     #[fail(display = "Received a non-success ({}) response from Sonos", 0)]
     BadResponse(u16),
     #[fail(display = "Couldn't find a device by the given identifier ({})", 0)]
     DeviceNotFound(String),
+    #[fail(display = "The given ID ({}) doesn't belong to the expected ContentDirectory container", 0)]
+    InvalidObjectId(String),
+    #[fail(display = "There's no item at position {} in the queue", 0)]
+    QueueItemNotFound(u64),
+    #[fail(display = "The speaker isn't playing from its queue")]
+    NotPlayingFromQueue,
+    #[fail(display = "The speaker doesn't have a {:?} input", 0)]
+    UnsupportedInput(AVSourceType),
+    #[fail(display = "The speaker doesn't support {:?}", 0)]
+    NotSupportedByDevice(SonosFeature),
+    #[fail(display = "The household has no music service called {}", 0)]
+    UnknownMusicService(String),
 }
 
 impl From<AVTransportError> for SonosError {
@@ -18,7 +42,7 @@ impl From<AVTransportError> for SonosError {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AVTransportError {
     /// No action by that name at this service.
     InvalidAction = 401,
@@ -107,3 +131,61 @@ impl From<u64> for AVTransportError {
         }
     }
 }
+
+impl TryFrom<&str> for AVTransportError {
+    type Error = SonosError;
+
+    /// Convert an error code as given in the errorCode of a SOAP fault (eg. 701).
+    fn try_from(code: &str) -> Result<Self, Self::Error> {
+        code.trim()
+            .parse::<u64>()
+            .map(AVTransportError::from)
+            .map_err(|_| SonosError::ParseError(format!("invalid error code {}", code)))
+    }
+}
+
+impl AVTransportError {
+    /// The UPnP error code for this error (eg. 701), or 0 for [AVTransportError::Unknown].
+    pub fn code(&self) -> u64 {
+        match self {
+            AVTransportError::Unknown => 0,
+            error => *error as u64,
+        }
+    }
+}
+
+impl fmt::Display for AVTransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            AVTransportError::InvalidAction => "invalid action",
+            AVTransportError::InvalidArgs => "invalid arguments",
+            AVTransportError::InvalidVar => "invalid state variable",
+            AVTransportError::ActionFailed => "action failed",
+            AVTransportError::TransitionNotAvailable => "transition not available",
+            AVTransportError::NoContents => "no contents",
+            AVTransportError::ReadError => "read error",
+            AVTransportError::FormatNotSupported => "format not supported for playback",
+            AVTransportError::TransportLocked => "transport is locked",
+            AVTransportError::WriteError => "write error",
+            AVTransportError::MediaNotWriteable => "media is protected or not writeable",
+            AVTransportError::RecordingFormatNotSupported => "format not supported for recording",
+            AVTransportError::MediaFull => "media is full",
+            AVTransportError::SeekModeNotSupported => "seek mode not supported",
+            AVTransportError::IllegalSeekTarget => "illegal seek target",
+            AVTransportError::PlayModeNotSupported => "play mode not supported",
+            AVTransportError::RecordQualityNotSupported => "record quality not supported",
+            AVTransportError::IllegalMimeType => "illegal MIME type",
+            AVTransportError::ContentBusy => "content is busy",
+            AVTransportError::PlaySpeedNotSupported => "play speed not supported",
+            AVTransportError::InvalidInstanceId => "invalid instance ID",
+            AVTransportError::NoDnsServer => "no DNS server",
+            AVTransportError::BadDomainName => "bad domain name",
+            AVTransportError::ServerError => "server error",
+            AVTransportError::Unknown => return f.write_str("unknown error"),
+        };
+
+        write!(f, "{} ({})", description, self.code())
+    }
+}
+
+impl std::error::Error for AVTransportError {}
@@ -0,0 +1,290 @@
+use crate::device::Speaker;
+use crate::error::*;
+use crate::subscription::SubscriptionManager;
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use failure::Error;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use tokio::sync::{broadcast, oneshot};
+use xmltree::{Element, XMLNode};
+
+/// How many unread events each route buffers before slow receivers start missing them.
+pub(crate) const CHANNEL_CAPACITY: usize = 32;
+
+type Dispatcher = Box<dyn Fn(Notification) + Send + Sync>;
+type Routes = Mutex<HashMap<String, Dispatcher>>;
+
+/// A single event (NOTIFY request) received from a speaker.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    /// SID of the subscription this event was sent for
+    pub sid: String,
+    /// Sequence number of this event, starting at 0 for each subscription
+    pub seq: u32,
+    /// Every property in the event, keyed by name (eg. LastChange or ZoneGroupState)
+    pub properties: HashMap<String, String>,
+    /// State variables parsed from the LastChange property, if there was one
+    pub last_change: Vec<ChangedVariable>,
+}
+
+/// A state variable that changed, as reported in a LastChange property.
+#[derive(Debug, Clone)]
+pub struct ChangedVariable {
+    /// Instance the variable belongs to (eg. the InstanceID or QueueID)
+    pub instance: String,
+    pub name: String,
+    /// Channel the variable applies to for per-channel variables (eg. Volume)
+    pub channel: Option<String>,
+    pub value: String,
+}
+
+/// HTTP server receiving events for GENA subscriptions.
+///
+/// Each subscription gets its own route on the server, created with [EventServer::add_route], and
+/// events sent to that route are dispatched to a broadcast channel. The server is stopped when
+/// this struct is dropped.
+///
+/// Every server comes with a [SubscriptionManager] which is used to keep the subscriptions made
+/// through it alive.
+pub struct EventServer {
+    local_addr: SocketAddr,
+    routes: Arc<Routes>,
+    subscriptions: SubscriptionManager,
+    next_route: AtomicU64,
+    _shutdown: oneshot::Sender<()>,
+}
+
+/// A route on an [EventServer], removed from the server when dropped.
+pub struct EventRoute<T> {
+    path: String,
+    port: u16,
+    sender: broadcast::Sender<T>,
+    routes: Weak<Routes>,
+}
+
+impl EventServer {
+    /// Start a server listening on a random port on every interface.
+    ///
+    /// This must be called from within a tokio runtime.
+    pub fn new() -> Result<Self, Error> {
+        Self::bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0))
+    }
+
+    /// Start a server listening on the given address.
+    ///
+    /// This must be called from within a tokio runtime.
+    pub fn bind(addr: SocketAddr) -> Result<Self, Error> {
+        let routes: Arc<Routes> = Arc::new(Mutex::new(HashMap::new()));
+
+        let service_routes = routes.clone();
+        let make_service = make_service_fn(move |_| {
+            let routes = service_routes.clone();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| handle(routes.clone(), req)))
+            }
+        });
+
+        let server = Server::try_bind(&addr)?.serve(make_service);
+        let local_addr = server.local_addr();
+
+        let (shutdown, rx) = oneshot::channel();
+        tokio::spawn(server.with_graceful_shutdown(async {
+            let _ = rx.await;
+        }));
+
+        debug!("Listening for events on {}", local_addr);
+
+        Ok(Self {
+            local_addr,
+            routes,
+            subscriptions: SubscriptionManager::new(),
+            next_route: AtomicU64::new(0),
+            _shutdown: shutdown,
+        })
+    }
+
+    /// The address this server is bound to.
+    ///
+    /// When bound to every interface this will be an unspecified address, use
+    /// [EventRoute::callback_url] to build a URL a speaker can reach.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// The manager renewing subscriptions made through this server.
+    pub fn subscriptions(&self) -> &SubscriptionManager {
+        &self.subscriptions
+    }
+
+    /// Add a route to this server which dispatches every event it receives to a broadcast channel.
+    pub fn add_raw_route(&self) -> EventRoute<Notification> {
+        self.add_route(Some)
+    }
+
+    /// Add a route to this server which converts events using `parse` before dispatching them to a
+    /// broadcast channel, events that `parse` returns `None` for are dropped.
+    pub fn add_route<T, F>(&self, parse: F) -> EventRoute<T>
+    where
+        T: Clone + Send + 'static,
+        F: Fn(Notification) -> Option<T> + Send + Sync + 'static,
+    {
+        let path = format!("/events/{}", self.next_route.fetch_add(1, Ordering::Relaxed));
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+
+        let dispatch_sender = sender.clone();
+        self.routes.lock().unwrap().insert(path.clone(), Box::new(move |notification| {
+            if let Some(event) = parse(notification) {
+                // an error here just means nobody is listening at the moment
+                let _ = dispatch_sender.send(event);
+            }
+        }));
+
+        EventRoute {
+            path,
+            port: self.local_addr.port(),
+            sender,
+            routes: Arc::downgrade(&self.routes),
+        }
+    }
+}
+
+impl<T> EventRoute<T> {
+    /// Path of this route on the server (eg. /events/0)
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Get a new receiver for the events sent to this route.
+    pub fn subscribe(&self) -> broadcast::Receiver<T> {
+        self.sender.subscribe()
+    }
+
+    /// Build the callback URL the given speaker should send events for this route to.
+    ///
+    /// The host is the address of the local interface used to reach the speaker.
+    pub fn callback_url(&self, speaker: &Speaker) -> Result<String, Error> {
+        let unspecified = match speaker.ip {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+
+        // connecting a UDP socket doesn't send anything, but does pick the interface to use
+        let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0))?;
+        socket.connect(SocketAddr::new(speaker.ip, 1400))?;
+
+        Ok(format!("http://{}{}", SocketAddr::new(socket.local_addr()?.ip(), self.port), self.path))
+    }
+}
+
+impl<T> Drop for EventRoute<T> {
+    fn drop(&mut self) {
+        if let Some(routes) = self.routes.upgrade() {
+            routes.lock().unwrap().remove(&self.path);
+        }
+    }
+}
+
+fn status(code: StatusCode) -> Response<Body> {
+    let mut resp = Response::new(Body::empty());
+    *resp.status_mut() = code;
+    resp
+}
+
+async fn handle(routes: Arc<Routes>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    if req.method().as_str() != "NOTIFY" {
+        return Ok(status(StatusCode::METHOD_NOT_ALLOWED));
+    }
+
+    let path = req.uri().path().to_string();
+
+    if !routes.lock().unwrap().contains_key(&path) {
+        return Ok(status(StatusCode::NOT_FOUND));
+    }
+
+    let header = |name| {
+        req.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let sid = header("SID").unwrap_or_default();
+    let seq = header("SEQ").and_then(|s| s.parse().ok()).unwrap_or_default();
+
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(body) => body,
+        Err(_) => return Ok(status(StatusCode::BAD_REQUEST)),
+    };
+
+    let notification = match parse_notification(sid, seq, body.as_ref()) {
+        Ok(notification) => notification,
+        Err(e) => {
+            error!("Failed to parse event sent to {}: {}", path, e);
+            return Ok(status(StatusCode::BAD_REQUEST));
+        }
+    };
+
+    if let Some(dispatch) = routes.lock().unwrap().get(&path) {
+        dispatch(notification);
+    }
+
+    Ok(status(StatusCode::OK))
+}
+
+/// Parse the body of a NOTIFY request.
+fn parse_notification(sid: String, seq: u32, body: &[u8]) -> Result<Notification, Error> {
+    let root = Element::parse(body)?;
+
+    let properties = root
+        .children
+        .iter()
+        .filter_map(XMLNode::as_element)
+        .filter(|el| el.name == "property")
+        .filter_map(|property| property.children.iter().find_map(XMLNode::as_element))
+        .map(|el| (el.name.clone(), el.get_text().map(|t| t.into_owned()).unwrap_or_default()))
+        .collect::<HashMap<_, _>>();
+
+    let last_change = match properties.get("LastChange") {
+        Some(last_change) => parse_last_change(last_change)?,
+        None => Vec::new(),
+    };
+
+    Ok(Notification {
+        sid,
+        seq,
+        properties,
+        last_change,
+    })
+}
+
+/// Parse the document in a LastChange property, which is an Event element containing every
+/// changed variable for an instance (eg. `<InstanceID val="0"><Volume channel="Master" val="10"/>`).
+pub(crate) fn parse_last_change(xml: &str) -> Result<Vec<ChangedVariable>, Error> {
+    let event = Element::parse(xml.as_bytes())?;
+
+    if event.name != "Event" {
+        return Err(SonosError::ParseError(format!("unexpected {} element in LastChange", event.name)).into());
+    }
+
+    Ok(event
+        .children
+        .iter()
+        .filter_map(XMLNode::as_element)
+        .flat_map(|instance| {
+            let id = instance.attributes.get("val").cloned().unwrap_or_default();
+            instance.children.iter().filter_map(XMLNode::as_element).map(move |var| (id.clone(), var))
+        })
+        .map(|(instance, var)| ChangedVariable {
+            instance,
+            name: var.name.clone(),
+            channel: var.attributes.get("channel").cloned(),
+            value: var.attributes.get("val").cloned().unwrap_or_default(),
+        })
+        .collect())
+}
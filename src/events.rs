@@ -0,0 +1,420 @@
+use crate::alarm::Alarm;
+use crate::device::{parse_duration, Speaker, TransportState};
+use crate::event_server::{EventServer, Notification, CHANNEL_CAPACITY};
+use crate::service::Service;
+use crate::subscription::EventSubscription;
+use crate::topology::{parse_zone_group_state, ZoneGroup};
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use failure::Error;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Receives typed events from a speaker for as long as it's held.
+///
+/// Dropping the receiver unsubscribes from the speaker and removes its route from the
+/// [EventServer].
+pub struct EventReceiver<T> {
+    receiver: broadcast::Receiver<T>,
+    // the subscription is declared before the route so we unsubscribe before the route goes away
+    _subscription: EventSubscription,
+    /// The [EventRoute](crate::EventRoute) events are received on, which isn't necessarily of
+    /// type `T` if events are converted in the background
+    _route: Box<dyn Any + Send + Sync>,
+}
+
+impl<T: Clone> EventReceiver<T> {
+    /// Wait for the next event from the speaker.
+    ///
+    /// Returns [RecvError::Lagged] if events were missed because they weren't received quickly
+    /// enough.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        self.receiver.recv().await
+    }
+}
+
+/// Subscribe to `endpoint` on the speaker, converting the events it sends using `parse`.
+async fn subscribe_to<T, F>(
+    speaker: &Speaker,
+    server: &EventServer,
+    endpoint: &str,
+    parse: F,
+) -> Result<EventReceiver<T>, Error>
+where
+    T: Clone + Send + 'static,
+    F: Fn(Notification) -> Option<T> + Send + Sync + 'static,
+{
+    let route = server.add_route(parse);
+    let receiver = route.subscribe();
+    let subscription = server
+        .subscriptions()
+        .subscribe(speaker, endpoint, &route.callback_url(speaker)?)
+        .await?;
+
+    Ok(EventReceiver {
+        receiver,
+        _subscription: subscription,
+        _route: Box::new(route),
+    })
+}
+
+/// A change to the AVTransport of a speaker, only the fields that changed are set.
+#[derive(Debug, Clone, Default)]
+pub struct TransportEvent {
+    pub transport_state: Option<TransportState>,
+    /// Play mode of the speaker (eg. NORMAL, SHUFFLE or REPEAT_ALL)
+    pub play_mode: Option<String>,
+    /// URI of the queue or stream currently loaded
+    pub transport_uri: Option<String>,
+    pub track_uri: Option<String>,
+    /// DIDL-Lite metadata for the current track
+    pub track_metadata: Option<String>,
+    pub track_duration: Option<Duration>,
+    /// Position of the current track in the queue, beginning at 1
+    pub track_number: Option<u64>,
+    pub number_of_tracks: Option<u64>,
+}
+
+pub type TransportEventReceiver = EventReceiver<TransportEvent>;
+
+impl TransportEvent {
+    fn from_notification(notification: Notification) -> Option<Self> {
+        let mut event = TransportEvent::default();
+        let mut changed = false;
+
+        for var in notification.last_change {
+            let value = var.value;
+
+            match var.name.as_str() {
+                "TransportState" => event.transport_state = TransportState::try_from(value.as_str()).ok(),
+                "CurrentPlayMode" => event.play_mode = Some(value),
+                "AVTransportURI" => event.transport_uri = Some(value),
+                "CurrentTrackURI" => event.track_uri = Some(value),
+                "CurrentTrackMetaData" => event.track_metadata = Some(value),
+                "CurrentTrackDuration" => event.track_duration = parse_duration(&value, "CurrentTrackDuration").ok(),
+                "CurrentTrack" => event.track_number = value.parse().ok(),
+                "NumberOfTracks" => event.number_of_tracks = value.parse().ok(),
+                _ => continue,
+            }
+
+            changed = true;
+        }
+
+        if changed {
+            Some(event)
+        } else {
+            None
+        }
+    }
+}
+
+/// A change to the rendering settings of a speaker.
+///
+/// Sonos resends every setting whenever one of them changes, so values that are the same as in
+/// the last event received are left unset.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VolumeEvent {
+    /// Volumes that changed, keyed by channel (eg. Master, LF or RF)
+    pub volume: HashMap<String, u8>,
+    pub mute: Option<bool>,
+    pub bass: Option<i8>,
+    pub treble: Option<i8>,
+    pub loudness: Option<bool>,
+}
+
+pub type VolumeEventReceiver = EventReceiver<VolumeEvent>;
+
+impl VolumeEvent {
+    fn from_notification(notification: Notification) -> Self {
+        let mut event = VolumeEvent::default();
+
+        for var in notification.last_change {
+            let master = var.channel.as_deref().is_none_or(|c| c == "Master");
+
+            match var.name.as_str() {
+                "Volume" => {
+                    if let (Some(channel), Ok(volume)) = (var.channel, var.value.parse()) {
+                        event.volume.insert(channel, volume);
+                    }
+                }
+                "Mute" if master => event.mute = Some(var.value == "1"),
+                "Bass" => event.bass = var.value.parse().ok(),
+                "Treble" => event.treble = var.value.parse().ok(),
+                "Loudness" if master => event.loudness = Some(var.value == "1"),
+                _ => {}
+            }
+        }
+
+        event
+    }
+
+    /// Remove every value that's the same in `last` and record the new values in it. Returns
+    /// `None` if nothing changed.
+    fn changes_since(mut self, last: &mut VolumeEvent) -> Option<Self> {
+        fn diff<T: PartialEq + Copy>(new: &mut Option<T>, last: &mut Option<T>) {
+            match new {
+                Some(_) if new == last => *new = None,
+                Some(_) => *last = *new,
+                None => {}
+            }
+        }
+
+        self.volume.retain(|channel, volume| last.volume.insert(channel.clone(), *volume) != Some(*volume));
+        diff(&mut self.mute, &mut last.mute);
+        diff(&mut self.bass, &mut last.bass);
+        diff(&mut self.treble, &mut last.treble);
+        diff(&mut self.loudness, &mut last.loudness);
+
+        if self == VolumeEvent::default() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+}
+
+/// A change to one of the queues on a speaker.
+///
+/// Events only say that a queue changed, not how, so callers should refetch the queue when they
+/// receive one.
+#[derive(Debug, Clone)]
+pub struct QueueEvent {
+    /// Queue that changed, the speaker's own queue is 0
+    pub queue_id: u32,
+    /// Counter incremented on every change to the queue, a gap between events means one was
+    /// missed
+    pub update_id: u32,
+}
+
+pub type QueueEventReceiver = EventReceiver<QueueEvent>;
+
+impl QueueEvent {
+    fn from_notification(notification: Notification) -> Option<Self> {
+        notification
+            .last_change
+            .into_iter()
+            .filter(|var| var.name == "UpdateID")
+            .find_map(|var| {
+                Some(QueueEvent {
+                    queue_id: var.instance.parse().ok()?,
+                    update_id: var.value.parse().ok()?,
+                })
+            })
+    }
+}
+
+/// A change to the groups in the household.
+#[derive(Debug, Clone)]
+pub struct TopologyEvent {
+    /// Every group in the household after the change
+    pub groups: Vec<ZoneGroup>,
+}
+
+pub type TopologyEventReceiver = EventReceiver<TopologyEvent>;
+
+impl TopologyEvent {
+    fn from_notification(notification: Notification) -> Option<Self> {
+        let state = notification.properties.get("ZoneGroupState")?;
+
+        match parse_zone_group_state(state) {
+            Ok(groups) => Some(TopologyEvent { groups }),
+            Err(e) => {
+                error!("Failed to parse ZoneGroupState from topology event: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// A change to the alarms in the household.
+#[derive(Debug, Clone)]
+pub enum AlarmEvent {
+    Created(Alarm),
+    Updated(Alarm),
+    /// An alarm was deleted, holding the ID it had
+    Deleted(u32),
+}
+
+pub type AlarmEventReceiver = EventReceiver<AlarmEvent>;
+
+impl AlarmEvent {
+    /// Get the events describing how the alarm list went from `old` to `new`.
+    fn between(old: &[Alarm], new: &[Alarm]) -> Vec<AlarmEvent> {
+        let deleted = old
+            .iter()
+            .filter(|alarm| !new.iter().any(|a| a.id == alarm.id))
+            .map(|alarm| AlarmEvent::Deleted(alarm.id));
+
+        let changed = new.iter().filter_map(|alarm| match old.iter().find(|a| a.id == alarm.id) {
+            None => Some(AlarmEvent::Created(alarm.clone())),
+            Some(previous) if previous != alarm => Some(AlarmEvent::Updated(alarm.clone())),
+            Some(_) => None,
+        });
+
+        deleted.chain(changed).collect()
+    }
+}
+
+/// A change to the household-level configuration stored in SystemProperties.
+#[derive(Debug, Clone)]
+pub struct SystemPropertiesEvent {
+    /// Identifier of the Sonos account the household belongs to
+    pub customer_id: Option<String>,
+    /// Counter incremented whenever a system property changes
+    pub update_id: Option<u32>,
+    /// Counter incremented whenever a protected system property changes
+    pub update_idx: Option<u32>,
+    /// Hash of the music service accounts in the household, changes when one is added or removed
+    pub third_party_hash: Option<String>,
+    /// Every property in the event, including ones not parsed above
+    pub properties: HashMap<String, String>,
+}
+
+pub type SystemPropertiesEventReceiver = EventReceiver<SystemPropertiesEvent>;
+
+impl SystemPropertiesEvent {
+    fn from_notification(notification: Notification) -> Option<Self> {
+        let properties = notification.properties;
+
+        Some(SystemPropertiesEvent {
+            customer_id: properties.get("CustomerID").cloned(),
+            update_id: properties.get("UpdateID").and_then(|v| v.parse().ok()),
+            update_idx: properties.get("UpdateIDX").and_then(|v| v.parse().ok()),
+            third_party_hash: properties.get("ThirdPartyHash").cloned(),
+            properties,
+        })
+    }
+}
+
+impl Speaker {
+    /// Subscribe to changes of this speaker's transport (play state, current track, etc).
+    pub async fn subscribe_to_transport_events(
+        &self,
+        server: &EventServer,
+    ) -> Result<TransportEventReceiver, Error> {
+        subscribe_to(
+            self,
+            server,
+            Service::AV_TRANSPORT.event_path(),
+            TransportEvent::from_notification,
+        ).await
+    }
+
+    /// Subscribe to changes of this speaker's volume, mute and EQ settings.
+    pub async fn subscribe_to_volume_events(
+        &self,
+        server: &EventServer,
+    ) -> Result<VolumeEventReceiver, Error> {
+        let last = Mutex::new(VolumeEvent::default());
+
+        subscribe_to(
+            self,
+            server,
+            Service::RENDERING_CONTROL.event_path(),
+            move |notification| {
+                VolumeEvent::from_notification(notification).changes_since(&mut last.lock().unwrap())
+            },
+        ).await
+    }
+
+    /// Subscribe to changes of the queues on this speaker.
+    pub async fn subscribe_to_queue_events(
+        &self,
+        server: &EventServer,
+    ) -> Result<QueueEventReceiver, Error> {
+        subscribe_to(
+            self,
+            server,
+            Service::QUEUE.event_path(),
+            QueueEvent::from_notification,
+        ).await
+    }
+
+    /// Subscribe to changes of the groups in this speaker's household, such as speakers joining or
+    /// leaving groups.
+    pub async fn subscribe_to_zone_topology_events(
+        &self,
+        server: &EventServer,
+    ) -> Result<TopologyEventReceiver, Error> {
+        subscribe_to(
+            self,
+            server,
+            Service::ZONE_GROUP_TOPOLOGY.event_path(),
+            TopologyEvent::from_notification,
+        ).await
+    }
+
+    /// Subscribe to changes of the household-level configuration stored in SystemProperties.
+    pub async fn subscribe_to_system_properties_events(
+        &self,
+        server: &EventServer,
+    ) -> Result<SystemPropertiesEventReceiver, Error> {
+        subscribe_to(
+            self,
+            server,
+            Service::SYSTEM_PROPERTIES.event_path(),
+            SystemPropertiesEvent::from_notification,
+        ).await
+    }
+
+    /// Subscribe to alarms being created, updated or deleted in this speaker's household.
+    ///
+    /// Sonos only sends the version of the alarm list in its events, so the alarm list is fetched
+    /// from the speaker whenever it changes to work out what happened.
+    pub async fn subscribe_to_alarm_events(
+        &self,
+        server: &EventServer,
+    ) -> Result<AlarmEventReceiver, Error> {
+        let route = server.add_raw_route();
+        let mut notifications = route.subscribe();
+        let subscription = server
+            .subscriptions()
+            .subscribe(self, Service::ALARM_CLOCK.event_path(), &route.callback_url(self)?)
+            .await?;
+
+        let (mut alarms, mut version) = self.list_alarms().await?;
+        let (sender, receiver) = broadcast::channel(CHANNEL_CAPACITY);
+        let speaker = self.clone();
+
+        // runs until the route is dropped along with the receiver
+        tokio::spawn(async move {
+            loop {
+                match notifications.recv().await {
+                    Ok(notification) => {
+                        if notification.properties.get("AlarmListVersion") == Some(&version) {
+                            continue;
+                        }
+                    }
+                    // if we missed an event we'll just check the alarm list again
+                    Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => return,
+                }
+
+                let (new_alarms, new_version) = match speaker.list_alarms().await {
+                    Ok(list) => list,
+                    Err(e) => {
+                        error!("Failed to fetch alarms from {}: {}", speaker.ip, e);
+                        continue;
+                    }
+                };
+
+                for event in AlarmEvent::between(&alarms, &new_alarms) {
+                    let _ = sender.send(event);
+                }
+
+                alarms = new_alarms;
+                version = new_version;
+            }
+        });
+
+        Ok(EventReceiver {
+            receiver,
+            _subscription: subscription,
+            _route: Box::new(route),
+        })
+    }
+}
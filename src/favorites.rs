@@ -0,0 +1,137 @@
+use crate::device::{get_child_element_text, get_child_text, xml_escape, Speaker};
+use crate::error::*;
+use crate::service::Service;
+
+use failure::Error;
+use futures::prelude::*;
+use xmltree::Element;
+
+/// ObjectID of the household's Sonos favorites in the ContentDirectory.
+const FAVORITES_OBJECT_ID: &str = "FV:2";
+
+/// ObjectID of the TuneIn stations bookmarked in the Sonos app.
+const RADIO_FAVORITES_OBJECT_ID: &str = "R:0/0";
+
+/// How many favorites are requested from the speaker at a time by [Speaker::favorites].
+const PAGE_SIZE: u32 = 100;
+
+/// An item saved to the household's Sonos favorites.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SonosFavorite {
+    /// ObjectID of the favorite (eg. FV:2/12)
+    pub id: String,
+    pub title: String,
+    /// Description shown under the title in the Sonos app (eg. the name of the music service)
+    pub description: Option<String>,
+    pub uri: Option<String>,
+    /// DIDL-Lite metadata that needs to be passed along with `uri` when playing the favorite
+    pub metadata: Option<String>,
+    pub album_art_uri: Option<String>,
+}
+
+/// A TuneIn radio station bookmarked in the Sonos app.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadioFavorite {
+    /// ObjectID of the bookmark (eg. R:0/0/3)
+    pub id: String,
+    pub title: String,
+    /// Stream URI of the station (eg. x-sonosapi-stream:s12345?sid=254)
+    pub uri: String,
+    pub logo_uri: Option<String>,
+    /// Whether the station has a stream that can be played.
+    ///
+    /// Sonos doesn't report whether a station is currently broadcasting, so this is only false
+    /// for bookmarks that the speaker has no stream URI for.
+    pub is_online: bool,
+}
+
+fn parse_favorite(el: &Element) -> Result<Option<SonosFavorite>, Error> {
+    Ok(Some(SonosFavorite {
+        id: el.attributes.get("id").cloned().unwrap_or_default(),
+        title: get_child_text(el, "title").unwrap_or_default(),
+        description: get_child_text(el, "description"),
+        uri: get_child_text(el, "res"),
+        metadata: get_child_text(el, "resMD"),
+        album_art_uri: get_child_text(el, "albumArtURI"),
+    }))
+}
+
+fn parse_radio_favorite(el: &Element) -> Result<Option<RadioFavorite>, Error> {
+    let uri = get_child_text(el, "res").unwrap_or_default();
+
+    Ok(Some(RadioFavorite {
+        id: el.attributes.get("id").cloned().unwrap_or_default(),
+        title: get_child_text(el, "title").unwrap_or_default(),
+        is_online: !uri.is_empty(),
+        uri,
+        logo_uri: get_child_text(el, "albumArtURI"),
+    }))
+}
+
+impl Speaker {
+    /// Stream the household's Sonos favorites, fetching `page_size` of them at a time as the
+    /// stream is polled.
+    pub fn favorites_stream(
+        &self,
+        page_size: u32,
+    ) -> impl Stream<Item = Result<SonosFavorite, Error>> + '_ {
+        self.browse_stream(FAVORITES_OBJECT_ID.to_string(), page_size, parse_favorite)
+    }
+
+    /// Get every one of the household's Sonos favorites.
+    pub async fn favorites(&self) -> Result<Vec<SonosFavorite>, Error> {
+        self.favorites_stream(PAGE_SIZE).try_collect().await
+    }
+
+    /// Get every TuneIn radio station bookmarked in the Sonos app.
+    pub async fn radio_favorites(&self) -> Result<Vec<RadioFavorite>, Error> {
+        self.browse_stream(RADIO_FAVORITES_OBJECT_ID.to_string(), PAGE_SIZE, parse_radio_favorite)
+            .try_collect()
+            .await
+    }
+
+    /// Add a URI to the household's Sonos favorites, returning the ID of the new favorite.
+    ///
+    /// `metadata` is the DIDL-Lite document describing the URI, the same as would be passed when
+    /// playing it.
+    pub async fn add_to_favorites(&self, uri: &str, title: &str, metadata: &str) -> Result<String, Error> {
+        let didl = format!(
+            r#"<DIDL-Lite xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/" xmlns:r="urn:schemas-rinconnetworks-com:metadata-1-0/" xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/"><item><dc:title>{title}</dc:title><upnp:class>object.itemobject.item.sonos-favorite</upnp:class><r:ordinal>0</r:ordinal><res>{uri}</res><r:type>instantPlay</r:type><r:resMD>{metadata}</r:resMD></item></DIDL-Lite>"#,
+            title = xml_escape(title),
+            uri = xml_escape(uri),
+            metadata = xml_escape(metadata),
+        );
+
+        let resp = self.soap(
+            &Service::CONTENT_DIRECTORY,
+            "CreateObject",
+            &format!(
+                "<ContainerID>{}</ContainerID><Elements>{}</Elements>",
+                FAVORITES_OBJECT_ID,
+                xml_escape(&didl)
+            ),
+            false,
+        ).await?;
+
+        Ok(get_child_element_text(&resp, "ObjectID")?.into_owned())
+    }
+
+    /// Remove a favorite from the household's Sonos favorites by its ID (eg. FV:2/12).
+    ///
+    /// Returns [SonosError::InvalidObjectId] if the ID isn't of a favorite, so that other items in
+    /// the ContentDirectory can't be deleted by mistake.
+    pub async fn remove_from_favorites(&self, id: &str) -> Result<(), Error> {
+        if !id.starts_with(&format!("{}/", FAVORITES_OBJECT_ID)) {
+            return Err(SonosError::InvalidObjectId(id.to_string()).into());
+        }
+
+        self.soap(
+            &Service::CONTENT_DIRECTORY,
+            "DestroyObject",
+            &format!("<ObjectID>{}</ObjectID>", xml_escape(id)),
+            false,
+        ).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+use crate::device::{request_error, Speaker, SPEAKER_HTTP_CLIENT};
+use crate::error::*;
+use crate::service::Service;
+
+use std::fmt;
+use std::time::Duration;
+
+use failure::Error;
+use reqwest::header::HeaderMap;
+use reqwest::Method;
+
+/// Identifies a GENA event subscription made by [Speaker::subscribe].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SubscriptionId {
+    endpoint: String,
+    sid: String,
+}
+
+impl SubscriptionId {
+    /// Identify the subscription to `endpoint` the speaker gave the given SID.
+    pub fn new(endpoint: &str, sid: &str) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            sid: sid.to_string(),
+        }
+    }
+
+    /// The endpoint on the speaker the subscription was made to (eg. MediaRenderer/AVTransport/Event).
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// The SID the speaker identifies the subscription by (eg.
+    /// uuid:RINCON_000E58000000001400_sub0000000001).
+    pub fn sid(&self) -> &str {
+        &self.sid
+    }
+}
+
+impl fmt::Display for SubscriptionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.sid)
+    }
+}
+
+/// Get the timeout granted by the speaker from a `Second-1800` style TIMEOUT header.
+fn granted_timeout(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get("TIMEOUT")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Second-")?
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+impl Speaker {
+    /// Subscribe to events from the given endpoint on this speaker (eg.
+    /// MediaRenderer/AVTransport/Event), asking for them to be sent to `callback_url` for
+    /// `timeout_secs` seconds.
+    ///
+    /// The subscription has to be renewed with [Speaker::renew_subscription] before it times out
+    /// for events to keep being sent.
+    pub async fn subscribe(
+        &self,
+        endpoint: &str,
+        callback_url: &str,
+        timeout_secs: u32,
+    ) -> Result<SubscriptionId, Error> {
+        let (sid, _) = self.subscribe_for(endpoint, callback_url, timeout_secs).await?;
+
+        Ok(sid)
+    }
+
+    /// Subscribe to events from the given endpoint, returning the SID of the subscription along
+    /// with how long the speaker has granted it for.
+    pub(crate) async fn subscribe_for(
+        &self,
+        endpoint: &str,
+        callback_url: &str,
+        timeout_secs: u32,
+    ) -> Result<(SubscriptionId, Duration), Error> {
+        let resp = SPEAKER_HTTP_CLIENT
+            .request(Method::from_bytes(b"SUBSCRIBE")?, self.url(endpoint))
+            .header("CALLBACK", format!("<{}>", callback_url))
+            .header("NT", "upnp:event")
+            .header("TIMEOUT", format!("Second-{}", timeout_secs))
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        if !resp.status().is_success() {
+            return Err(SonosError::BadResponse(resp.status().as_u16()).into());
+        }
+
+        let sid = resp
+            .headers()
+            .get("SID")
+            .and_then(|sid| sid.to_str().ok())
+            .ok_or_else(|| SonosError::ParseError("missing SID header".to_string()))?
+            .to_string();
+        let granted = granted_timeout(resp.headers())
+            .unwrap_or_else(|| Duration::from_secs(timeout_secs.into()));
+
+        Ok((SubscriptionId::new(endpoint, &sid), granted))
+    }
+
+    /// Subscribe to AVTransport events from this speaker, returning the SID the speaker gave the
+    /// subscription.
+    ///
+    /// This is [Speaker::subscribe] for the AVTransport event endpoint, for callers keeping track
+    /// of subscriptions by SID themselves.
+    pub async fn av_transport_subscribe(&self, callback_url: &str, timeout_secs: u32) -> Result<String, Error> {
+        let sid = self.subscribe(Service::AV_TRANSPORT.event_path(), callback_url, timeout_secs).await?;
+
+        Ok(sid.sid().to_string())
+    }
+
+    /// Subscribe to RenderingControl (volume and EQ) events from this speaker, returning the SID
+    /// the speaker gave the subscription.
+    ///
+    /// See [Speaker::av_transport_subscribe].
+    pub async fn rendering_control_subscribe(&self, callback_url: &str, timeout_secs: u32) -> Result<String, Error> {
+        let sid = self.subscribe(Service::RENDERING_CONTROL.event_path(), callback_url, timeout_secs).await?;
+
+        Ok(sid.sid().to_string())
+    }
+
+    /// Renew a subscription for another `timeout_secs` seconds, returning how long the speaker
+    /// has granted it for.
+    pub async fn renew_subscription(
+        &self,
+        sid: &SubscriptionId,
+        timeout_secs: u32,
+    ) -> Result<Duration, Error> {
+        let resp = SPEAKER_HTTP_CLIENT
+            .request(Method::from_bytes(b"SUBSCRIBE")?, self.url(&sid.endpoint))
+            .header("SID", sid.sid())
+            .header("TIMEOUT", format!("Second-{}", timeout_secs))
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        if !resp.status().is_success() {
+            return Err(SonosError::BadResponse(resp.status().as_u16()).into());
+        }
+
+        Ok(granted_timeout(resp.headers()).unwrap_or_else(|| Duration::from_secs(timeout_secs.into())))
+    }
+
+    /// Cancel a subscription.
+    pub async fn unsubscribe(&self, sid: &SubscriptionId) -> Result<(), Error> {
+        let resp = SPEAKER_HTTP_CLIENT
+            .request(Method::from_bytes(b"UNSUBSCRIBE")?, self.url(&sid.endpoint))
+            .header("SID", sid.sid())
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        if !resp.status().is_success() {
+            return Err(SonosError::BadResponse(resp.status().as_u16()).into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use crate::device::Speaker;
+use crate::discovery::discover;
+use crate::topology::ZoneGroup;
+
+use failure::Error;
+use futures::future;
+
+/// Every speaker in a household along with how they're grouped.
+#[derive(Debug, Clone)]
+pub struct Household {
+    pub speakers: Vec<Speaker>,
+    pub groups: Vec<ZoneGroup>,
+}
+
+impl Household {
+    /// Discover the speakers on the current network and the groups they're in.
+    ///
+    /// This assumes there's only one household on the network.
+    pub async fn discover() -> Result<Household, Error> {
+        let speakers = discover().await?;
+        let groups = match speakers.first() {
+            Some(speaker) => speaker.zone_group_state().await?,
+            None => Vec::new(),
+        };
+
+        Ok(Household { speakers, groups })
+    }
+
+    /// Get the household the given speaker is in, fetching every other speaker in it.
+    ///
+    /// Speakers that can't be reached (eg. because they've just been unplugged) are left out, this
+    /// only fails if the coordinator of the given speaker's group can't be reached.
+    pub async fn from_speaker(speaker: &Speaker) -> Result<Household, Error> {
+        let groups = speaker.zone_group_state().await?;
+        let coordinator = groups
+            .iter()
+            .find(|group| group.members.iter().any(|member| member.uuid == speaker.uuid))
+            .map(|group| group.coordinator.as_str());
+
+        let members: Vec<_> = groups.iter().flat_map(|group| &group.members).collect();
+        let results = future::join_all(members.iter().map(|member| Speaker::from_ip(member.ip))).await;
+
+        let mut speakers = Vec::with_capacity(members.len());
+
+        for (member, result) in members.into_iter().zip(results) {
+            match result {
+                Ok(speaker) => speakers.push(speaker),
+                Err(e) if coordinator == Some(member.uuid.as_str()) => return Err(e),
+                Err(e) => debug!("Leaving {} out of the household: {}", member.name, e),
+            }
+        }
+
+        Ok(Household { speakers, groups })
+    }
+
+    /// Get the speakers coordinating each group in the household.
+    pub fn coordinators(&self) -> Vec<&Speaker> {
+        self.speakers
+            .iter()
+            .filter(|speaker| self.groups.iter().any(|group| group.coordinator == speaker.uuid))
+            .collect()
+    }
+
+    /// Get the speakers of the given model (eg. Sonos Era 300).
+    pub fn find_speakers_by_model(&self, model: &str) -> Vec<&Speaker> {
+        self.speakers.iter().filter(|speaker| speaker.model == model).collect()
+    }
+
+    /// Get the speakers with the given model number (eg. S39 for the Era 100).
+    pub fn find_speakers_by_model_number(&self, model_number: &str) -> Vec<&Speaker> {
+        self.speakers
+            .iter()
+            .filter(|speaker| speaker.model_number == model_number)
+            .collect()
+    }
+
+    /// Pause every group in the household at once.
+    ///
+    /// Groups that fail to pause (eg. because they're already stopped) are ignored.
+    pub async fn pause_all(&self) -> Result<(), Error> {
+        let coordinators = self.coordinators();
+        let results = future::join_all(coordinators.iter().map(|speaker| speaker.pause())).await;
+
+        for (speaker, result) in coordinators.iter().zip(results) {
+            if let Err(e) = result {
+                debug!("Failed to pause {}: {}", speaker.name, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resume every group in the household at once.
+    ///
+    /// Groups that fail to play (eg. because they have nothing to play) are ignored.
+    pub async fn play_all(&self) -> Result<(), Error> {
+        let coordinators = self.coordinators();
+        let results = future::join_all(coordinators.iter().map(|speaker| speaker.play())).await;
+
+        for (speaker, result) in coordinators.iter().zip(results) {
+            if let Err(e) = result {
+                debug!("Failed to play {}: {}", speaker.name, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set the volume of every group in the household at once from 0-100.
+    ///
+    /// Speakers keep their volume relative to the others in their group, see
+    /// [Speaker::set_group_volume].
+    pub async fn set_all_volumes(&self, volume: u8) -> Result<(), Error> {
+        future::try_join_all(
+            self.coordinators()
+                .into_iter()
+                .map(|speaker| speaker.set_group_volume(volume)),
+        ).await?;
+
+        Ok(())
+    }
+}
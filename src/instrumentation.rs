@@ -0,0 +1,74 @@
+use crate::error::*;
+
+use std::time::Duration;
+
+use failure::Error;
+use xmltree::Element;
+
+/// Get the kind of error a SOAP call failed with, ie. the [SonosError] variant (or Http for
+/// errors making the request at all).
+fn error_kind(error: &Error) -> &'static str {
+    if let Some(error) = error.downcast_ref::<SonosError>() {
+        match error {
+            SonosError::AVTransportError(_) => "AVTransportError",
+            SonosError::ParseError(_) => "ParseError",
+            SonosError::DeviceUnreachable => "DeviceUnreachable",
+            SonosError::Timeout => "Timeout",
+            SonosError::ConnectionRefused => "ConnectionRefused",
+            SonosError::NetworkUnreachable => "NetworkUnreachable",
+            SonosError::BadResponse(_) => "BadResponse",
+            SonosError::DeviceNotFound(_) => "DeviceNotFound",
+            SonosError::InvalidObjectId(_) => "InvalidObjectId",
+            SonosError::QueueItemNotFound(_) => "QueueItemNotFound",
+            SonosError::NotPlayingFromQueue => "NotPlayingFromQueue",
+            SonosError::UnsupportedInput(_) => "UnsupportedInput",
+            SonosError::NotSupportedByDevice(_) => "NotSupportedByDevice",
+            SonosError::UnknownMusicService(_) => "UnknownMusicService",
+        }
+    } else if error.downcast_ref::<reqwest::Error>().is_some() {
+        "Http"
+    } else {
+        "Other"
+    }
+}
+
+/// Get a label for the error a SOAP call failed with, without any details that would give the
+/// metric an unbounded number of label values. This is the UPnP error for faults returned by the
+/// speaker, and the same as [error_kind] otherwise.
+fn error_label(error: &Error) -> String {
+    match error.downcast_ref::<SonosError>() {
+        Some(SonosError::AVTransportError(e)) => format!("{:?}", e),
+        _ => error_kind(error).to_string(),
+    }
+}
+
+/// Record the duration and outcome of a SOAP call.
+///
+/// Every call is counted in `sonos.soap.calls` and its duration recorded to the
+/// `sonos.soap.duration_ms` histogram, both labelled with the `action` and whether it succeeded.
+/// Failures are also counted in `sonos.soap.errors`, labelled with the `action`, the `kind` of
+/// [SonosError] and the `error` itself.
+pub(crate) fn record_soap_call(action: &str, elapsed: Duration, result: &Result<Element, Error>) {
+    let outcome = if result.is_ok() { "success" } else { "error" };
+
+    metrics::counter!(
+        "sonos.soap.calls",
+        "action" => action.to_string(),
+        "result" => outcome
+    ).increment(1);
+
+    metrics::histogram!(
+        "sonos.soap.duration_ms",
+        "action" => action.to_string(),
+        "result" => outcome
+    ).record(elapsed.as_secs_f64() * 1000.0);
+
+    if let Err(e) = result {
+        metrics::counter!(
+            "sonos.soap.errors",
+            "action" => action.to_string(),
+            "kind" => error_kind(e),
+            "error" => error_label(e)
+        ).increment(1);
+    }
+}
@@ -1,14 +1,105 @@
+// failure's derive generates its impls inside an anonymous const
+#![allow(non_local_definitions)]
+
+// log is used unless tracing has been enabled as well
+#[cfg(all(feature = "log", not(feature = "tracing")))]
 #[macro_use] extern crate log;
+#[cfg(feature = "tracing")]
+#[macro_use] extern crate tracing;
 #[macro_use] extern crate failure;
 #[macro_use] extern crate lazy_static;
 
+// with neither enabled the arguments are still type-checked so they don't become unused
+#[cfg(not(any(feature = "log", feature = "tracing")))]
+macro_rules! debug {
+    ($($arg:tt)*) => { if false { let _ = format!($($arg)*); } };
+}
+
+#[cfg(not(any(feature = "log", feature = "tracing")))]
+macro_rules! error {
+    ($($arg:tt)*) => { if false { let _ = format!($($arg)*); } };
+}
+
 mod discovery;
 mod device;
 mod error;
+mod topology;
+mod alarm;
+mod announce;
+mod audio_clip;
+mod av_input;
+mod builder;
+mod clock;
+mod description;
+mod favorites;
+mod gena;
+mod household;
+mod library;
+mod model;
+mod music_services;
+mod playlist;
+mod recent;
+mod service;
+mod soap;
+mod sound;
+mod tunein;
+mod updates;
+mod zone_info;
+#[cfg(feature = "metrics")]
+mod instrumentation;
+#[cfg(feature = "watch")]
+mod state;
+#[cfg(feature = "events")]
+mod subscription;
+#[cfg(feature = "events")]
+mod event_server;
+#[cfg(feature = "events")]
+mod events;
+#[cfg(feature = "events")]
+mod zone;
 
 pub use device::Speaker;
 pub use device::Track;
 pub use device::TransportState;
+pub use device::ProtocolInfo;
+pub use device::AudioSource;
+pub use device::QueuePolicy;
 pub use error::*;
+pub use topology::ZoneGroup;
+pub use topology::ZoneMember;
+pub use topology::SpeakerType;
+pub use alarm::{Alarm, AlarmClockInfo};
+pub use av_input::{AVSource, AVSourceType};
+pub use builder::SpeakerBuilder;
+pub use clock::ClockFormat;
+pub use description::StateVariable;
+pub use favorites::{RadioFavorite, SonosFavorite};
+pub use gena::SubscriptionId;
+pub use household::Household;
+pub use library::{LibraryCategory, LibraryItem, MusicLibrary};
+pub use model::{ModelFamily, SonosFeature};
+pub use music_services::MusicService;
+pub use playlist::SonosPlaylist;
+pub use recent::RecentItem;
+pub use service::{Service, SpeakerEndpoint};
+pub use soap::SoapRequest;
+pub use sound::{BassCapabilities, SoundSettings};
+pub use tunein::RadioStation;
+pub use updates::PendingUpdate;
+pub use zone_info::ZoneInfo;
+#[cfg(feature = "watch")]
+pub use state::SpeakerState;
+#[cfg(feature = "events")]
+pub use subscription::{EventSubscription, SubscriptionManager};
+#[cfg(feature = "events")]
+pub use event_server::{ChangedVariable, EventRoute, EventServer, Notification};
+#[cfg(feature = "events")]
+pub use events::{
+    AlarmEvent, AlarmEventReceiver, EventReceiver, QueueEvent, QueueEventReceiver,
+    SystemPropertiesEvent, SystemPropertiesEventReceiver, TopologyEvent, TopologyEventReceiver,
+    TransportEvent, TransportEventReceiver, VolumeEvent, VolumeEventReceiver,
+};
+#[cfg(feature = "events")]
+pub use zone::Zone;
 
 pub use discovery::discover;
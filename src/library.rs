@@ -0,0 +1,282 @@
+use crate::device::{get_child_element_text, get_child_text, xml_escape, Speaker};
+use crate::error::*;
+use crate::service::Service;
+
+use failure::Error;
+use futures::prelude::*;
+use futures::stream;
+use xmltree::{Element, XMLNode};
+
+/// How many items are requested from the speaker at a time when browsing.
+const PAGE_SIZE: u32 = 100;
+
+/// An item in the music library.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LibraryItem {
+    Artist {
+        id: String,
+        title: String,
+        uri: Option<String>,
+    },
+    Album {
+        id: String,
+        title: String,
+        uri: Option<String>,
+        artist: Option<String>,
+        album_art_uri: Option<String>,
+        /// Number of tracks on the album, if the speaker reported it
+        track_count: Option<u32>,
+    },
+    Track {
+        id: String,
+        title: String,
+        uri: Option<String>,
+        artist: Option<String>,
+        album: Option<String>,
+        album_art_uri: Option<String>,
+    },
+    Playlist {
+        id: String,
+        title: String,
+        uri: Option<String>,
+        /// Number of tracks in the playlist, if the speaker reported it
+        track_count: Option<u32>,
+    },
+}
+
+/// The parts of the music library that can be browsed and searched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LibraryCategory {
+    Artists,
+    Albums,
+    Tracks,
+}
+
+impl LibraryCategory {
+    fn object_id(self) -> &'static str {
+        match self {
+            LibraryCategory::Artists => "A:ALBUMARTIST",
+            LibraryCategory::Albums => "A:ALBUM",
+            LibraryCategory::Tracks => "A:TRACKS",
+        }
+    }
+}
+
+/// Parse an item from a DIDL-Lite document, returning `None` for classes of item we don't know.
+fn parse_library_item(el: &Element) -> Result<Option<LibraryItem>, Error> {
+    let id = el.attributes.get("id").cloned().unwrap_or_default();
+    let title = get_child_text(el, "title").unwrap_or_default();
+    let uri = get_child_text(el, "res");
+    let class = get_child_text(el, "class").unwrap_or_default();
+    let track_count = el.attributes.get("childCount").and_then(|count| count.parse().ok());
+
+    Ok(Some(match class.as_str() {
+        "object.container.person.musicArtist" => LibraryItem::Artist { id, title, uri },
+        "object.container.album.musicAlbum" => LibraryItem::Album {
+            id,
+            title,
+            uri,
+            artist: get_child_text(el, "creator"),
+            album_art_uri: get_child_text(el, "albumArtURI"),
+            track_count,
+        },
+        "object.item.audioItem.musicTrack" => LibraryItem::Track {
+            id,
+            title,
+            uri,
+            artist: get_child_text(el, "creator"),
+            album: get_child_text(el, "album"),
+            album_art_uri: get_child_text(el, "albumArtURI"),
+        },
+        "object.container.playlistContainer" => LibraryItem::Playlist { id, title, uri, track_count },
+        _ => {
+            debug!("Skipping library item {} of unknown class {}", id, class);
+            return Ok(None);
+        }
+    }))
+}
+
+/// Percent-encode a search term the way Sonos expects it in an ObjectID.
+fn encode_search_term(term: &str) -> String {
+    term.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+impl Speaker {
+    /// Browse a single page of the direct children of `object_id` in the ContentDirectory,
+    /// returning the items along with the total number of children.
+    pub(crate) async fn browse_page(
+        &self,
+        object_id: &str,
+        start: u32,
+        count: u32,
+    ) -> Result<(Vec<Element>, u32), Error> {
+        let resp = self.soap(
+            &Service::CONTENT_DIRECTORY,
+            "Browse",
+            &format!(
+                "<ObjectID>{}</ObjectID><BrowseFlag>BrowseDirectChildren</BrowseFlag>\
+                 <Filter>*</Filter><StartingIndex>{}</StartingIndex>\
+                 <RequestedCount>{}</RequestedCount><SortCriteria></SortCriteria>",
+                xml_escape(object_id),
+                start,
+                count
+            ),
+            false,
+        ).await?;
+
+        let results = Element::parse(get_child_element_text(&resp, "Result")?.as_bytes())?;
+        let items = results
+            .children
+            .into_iter()
+            .filter_map(|node| match node {
+                XMLNode::Element(el) => Some(el),
+                _ => None,
+            })
+            .collect();
+
+        Ok((items, get_child_element_text(&resp, "TotalMatches")?.parse()?))
+    }
+
+    /// Get the DIDL-Lite element describing `object_id` itself in the ContentDirectory.
+    pub(crate) async fn browse_metadata(&self, object_id: &str) -> Result<Element, Error> {
+        let resp = self.soap(
+            &Service::CONTENT_DIRECTORY,
+            "Browse",
+            &format!(
+                "<ObjectID>{}</ObjectID><BrowseFlag>BrowseMetadata</BrowseFlag>\
+                 <Filter>*</Filter><StartingIndex>0</StartingIndex>\
+                 <RequestedCount>1</RequestedCount><SortCriteria></SortCriteria>",
+                xml_escape(object_id)
+            ),
+            false,
+        ).await?;
+
+        let results = Element::parse(get_child_element_text(&resp, "Result")?.as_bytes())?;
+
+        results
+            .children
+            .into_iter()
+            .find_map(|node| match node {
+                XMLNode::Element(el) => Some(el),
+                _ => None,
+            })
+            .ok_or_else(|| SonosError::InvalidObjectId(object_id.to_string()).into())
+    }
+
+    /// Stream every direct child of `object_id` in the ContentDirectory as the DIDL-Lite `item`
+    /// or `container` element describing it, fetching them a page at a time as the stream is
+    /// polled.
+    pub fn browse_content_directory(
+        &self,
+        object_id: &str,
+    ) -> impl Stream<Item = Result<Element, Error>> + '_ {
+        self.browse_elements(object_id.to_string(), PAGE_SIZE)
+    }
+
+    /// Stream every direct child of `object_id` in the ContentDirectory, fetching `page_size`
+    /// items at a time as the stream is polled.
+    fn browse_elements(
+        &self,
+        object_id: String,
+        page_size: u32,
+    ) -> impl Stream<Item = Result<Element, Error>> + '_ {
+        stream::try_unfold(Some(0), move |start| {
+            let object_id = object_id.clone();
+
+            async move {
+                let start = match start {
+                    Some(start) => start,
+                    None => return Ok(None),
+                };
+
+                let (items, total) = self.browse_page(&object_id, start, page_size).await?;
+                let next = start + items.len() as u32;
+                let next = if items.is_empty() || next >= total { None } else { Some(next) };
+
+                Ok::<_, Error>(Some((items, next)))
+            }
+        })
+        .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Browse every direct child of `object_id` in the ContentDirectory, fetching `page_size`
+    /// items at a time as the stream is polled. Items `parse` returns `None` for are skipped.
+    pub(crate) fn browse_stream<'a, T, F>(
+        &'a self,
+        object_id: String,
+        page_size: u32,
+        mut parse: F,
+    ) -> impl Stream<Item = Result<T, Error>> + 'a
+    where
+        T: 'a,
+        F: FnMut(&Element) -> Result<Option<T>, Error> + 'a,
+    {
+        self.browse_elements(object_id, page_size)
+            .try_filter_map(move |el| future::ready(parse(&el)))
+    }
+
+    /// Get every direct child of `object_id` in the ContentDirectory (eg. A:ALBUM for every album
+    /// or SQ: for every Sonos playlist).
+    ///
+    /// Children that aren't artists, albums, tracks or playlists are skipped.
+    pub async fn content_directory_browse(&self, object_id: &str) -> Result<Vec<LibraryItem>, Error> {
+        self.browse_stream(object_id.to_string(), PAGE_SIZE, parse_library_item)
+            .try_collect()
+            .await
+    }
+
+    /// Get a handle for browsing the music library shared to this speaker's household.
+    pub fn music_library(&self) -> MusicLibrary<'_> {
+        MusicLibrary { speaker: self }
+    }
+}
+
+/// Browses and searches the music library of the [Speaker] that spawned this [MusicLibrary].
+pub struct MusicLibrary<'a> {
+    speaker: &'a Speaker,
+}
+
+impl<'a> MusicLibrary<'a> {
+    /// Stream every item in a category of the library.
+    pub fn browse(
+        &self,
+        category: LibraryCategory,
+    ) -> impl Stream<Item = Result<LibraryItem, Error>> + 'a {
+        self.speaker
+            .browse_stream(category.object_id().to_string(), PAGE_SIZE, parse_library_item)
+    }
+
+    /// Stream every artist in the library.
+    pub fn browse_artists(&self) -> impl Stream<Item = Result<LibraryItem, Error>> + 'a {
+        self.browse(LibraryCategory::Artists)
+    }
+
+    /// Stream every album in the library.
+    pub fn browse_albums(&self) -> impl Stream<Item = Result<LibraryItem, Error>> + 'a {
+        self.browse(LibraryCategory::Albums)
+    }
+
+    /// Stream every track in the library.
+    pub fn browse_tracks(&self) -> impl Stream<Item = Result<LibraryItem, Error>> + 'a {
+        self.browse(LibraryCategory::Tracks)
+    }
+
+    /// Stream the items in a category of the library whose name matches `term`.
+    pub fn search(
+        &self,
+        category: LibraryCategory,
+        term: &str,
+    ) -> impl Stream<Item = Result<LibraryItem, Error>> + 'a {
+        self.speaker.browse_stream(
+            format!("{}:{}", category.object_id(), encode_search_term(term)),
+            PAGE_SIZE,
+            parse_library_item,
+        )
+    }
+}
@@ -0,0 +1,175 @@
+use crate::device::Speaker;
+
+use self::ModelFamily::*;
+
+/// The kind of hardware a speaker is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelFamily {
+    /// Home theatre speakers placed by a TV (eg. Playbar, Beam, Arc)
+    Soundbar,
+    /// Mains powered speakers (eg. Play:1, One, Five)
+    StandaloneAmplified,
+    /// Battery powered speakers (eg. Move, Roam)
+    PortableAmplified,
+    /// Amplifiers for driving passive speakers (eg. Connect:Amp, Amp)
+    Amp,
+    /// Unamplified streamers for connecting to an existing stereo (eg. Connect, Port)
+    Streamer,
+    Sub,
+    /// A model this library doesn't know about yet
+    Unknown,
+}
+
+/// Optional capabilities that depend on a speaker's model or firmware, see
+/// [Speaker::supports_feature].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SonosFeature {
+    /// Playing audio on its own rather than only connecting other devices to the network
+    Playback,
+    /// Leaving its group with BecomeCoordinatorOfStandaloneGroup
+    StandaloneGroup,
+    /// Playing from a TV input
+    Tv,
+    /// Playing from an analogue line-in
+    LineIn,
+    NightMode,
+    SpeechEnhancement,
+    /// Serving the JSON status document read by `Speaker::status_json`
+    StatusJson,
+    /// Playing clips over the top of what's playing with [Speaker::play_audio_clip]
+    AudioClip,
+}
+
+/// Major firmware version of the first S2 release, which is needed for [SonosFeature::StatusJson]
+/// and [SonosFeature::AudioClip].
+const S2_FIRMWARE_MAJOR: u32 = 60;
+
+/// Model number of the Sonos Bridge.
+pub(crate) const BRIDGE_MODEL_NUMBER: &str = "ZB100";
+
+/// Model number of the Sonos Boost.
+pub(crate) const BOOST_MODEL_NUMBER: &str = "WD100";
+
+/// Models whose TV input is optical rather than HDMI ARC.
+const OPTICAL_TV_MODEL_NUMBERS: &[&str] = &["S9", "S11", "S36"];
+
+/// What a model of speaker is capable of, for the capabilities that can't be queried from the
+/// speaker itself.
+struct Model {
+    /// Model number as reported in the device description (eg. S14)
+    number: &'static str,
+    family: ModelFamily,
+    /// Whether the speaker has a TV input (HDMI ARC or optical)
+    tv: bool,
+    /// Whether the speaker has an analogue line-in
+    line_in: bool,
+}
+
+const fn model(number: &'static str, family: ModelFamily, tv: bool, line_in: bool) -> Model {
+    Model { number, family, tv, line_in }
+}
+
+/// Known models, anything not in here is assumed to support none of the optional capabilities.
+const MODELS: &[Model] = &[
+    model("ZP80", Streamer, false, true),              // Connect
+    model("ZP90", Streamer, false, true),              // Connect
+    model("ZP100", Amp, false, true),                  // Connect:Amp
+    model("ZP120", Amp, false, true),                  // Connect:Amp
+    model("S1", StandaloneAmplified, false, false),    // Play:1
+    model("S3", StandaloneAmplified, false, false),    // Play:3
+    model("S5", StandaloneAmplified, false, true),     // Play:5
+    model("S6", StandaloneAmplified, false, true),     // Play:5 (Gen 2)
+    model("S9", Soundbar, true, false),                // Playbar
+    model("S11", Soundbar, true, false),               // Playbase
+    model("S13", StandaloneAmplified, false, false),   // One
+    model("S14", Soundbar, true, false),               // Beam
+    model("S15", Streamer, false, true),               // Connect (Gen 2)
+    model("S16", Amp, true, true),                     // Amp
+    model("S17", PortableAmplified, false, false),     // Move
+    model("S18", StandaloneAmplified, false, false),   // One SL
+    model("S19", Soundbar, true, false),               // Arc
+    model("S21", StandaloneAmplified, false, true),    // Five
+    model("S23", Streamer, false, true),               // Port
+    model("S27", PortableAmplified, false, false),     // Roam
+    model("S31", Soundbar, true, false),               // Beam (Gen 2)
+    model("S36", Soundbar, true, false),               // Ray
+    model("S39", StandaloneAmplified, false, true),    // Era 100, line-in through the USB-C adapter
+    model("S41", StandaloneAmplified, false, true),    // Era 300, line-in through the USB-C adapter
+    model("Sub", Sub, false, false),                   // Sub
+];
+
+impl Speaker {
+    fn known_model(&self) -> Option<&'static Model> {
+        MODELS.iter().find(|model| model.number.eq_ignore_ascii_case(&self.model_number))
+    }
+
+    /// The kind of hardware this speaker is, based on its model number.
+    ///
+    /// Subs are also recognised by their model name (eg. Sonos Sub Mini), as every revision has
+    /// its own model number.
+    pub fn model_family(&self) -> ModelFamily {
+        match self.known_model() {
+            Some(model) => model.family,
+            None if self.model.split_whitespace().any(|word| word.eq_ignore_ascii_case("Sub")) => ModelFamily::Sub,
+            None => ModelFamily::Unknown,
+        }
+    }
+
+    /// Whether the device can play audio on its own, which isn't the case for devices that only
+    /// connect others to the network (Bridge and Boost) or that have to be bonded to another
+    /// speaker (Sub).
+    pub fn is_playback_device(&self) -> bool {
+        let number = self.model_number.as_str();
+
+        !number.eq_ignore_ascii_case(BRIDGE_MODEL_NUMBER)
+            && !number.eq_ignore_ascii_case(BOOST_MODEL_NUMBER)
+            && self.model_family() != ModelFamily::Sub
+    }
+
+    /// Whether the speaker has a TV input (HDMI ARC or optical).
+    pub fn supports_tv(&self) -> bool {
+        self.known_model().is_some_and(|model| model.tv)
+    }
+
+    /// Whether the speaker's TV input is optical rather than HDMI ARC, only meaningful for
+    /// speakers that [support a TV](Speaker::supports_tv) at all.
+    pub(crate) fn has_optical_tv_input(&self) -> bool {
+        OPTICAL_TV_MODEL_NUMBERS.iter().any(|number| number.eq_ignore_ascii_case(&self.model_number))
+    }
+
+    /// Whether the speaker has an analogue line-in.
+    pub fn supports_line_in(&self) -> bool {
+        self.known_model().is_some_and(|model| model.line_in)
+    }
+
+    /// Whether the speaker supports night mode, which is only available on speakers with a TV
+    /// input.
+    pub fn supports_night_mode(&self) -> bool {
+        self.supports_tv()
+    }
+
+    /// Whether the speaker supports speech enhancement, which is only available on speakers with
+    /// a TV input.
+    pub fn supports_speech_enhancement(&self) -> bool {
+        self.supports_tv()
+    }
+
+    /// Whether the speaker supports the given feature, based on its model number and firmware
+    /// version.
+    ///
+    /// This lets callers check up front rather than handling the error returned by the speaker
+    /// when a feature isn't supported.
+    pub fn supports_feature(&self, feature: SonosFeature) -> bool {
+        match feature {
+            SonosFeature::Playback => self.is_playback_device(),
+            // speakers bonded to another (eg. a Sub) are always part of that speaker's group
+            SonosFeature::StandaloneGroup => self.is_playback_device(),
+            SonosFeature::Tv => self.supports_tv(),
+            SonosFeature::LineIn => self.supports_line_in(),
+            SonosFeature::NightMode => self.supports_night_mode(),
+            SonosFeature::SpeechEnhancement => self.supports_speech_enhancement(),
+            SonosFeature::StatusJson => self.firmware_major() >= S2_FIRMWARE_MAJOR,
+            SonosFeature::AudioClip => self.is_playback_device() && self.firmware_major() >= S2_FIRMWARE_MAJOR,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::device::{get_child_element_text, Speaker};
+use crate::error::*;
+use crate::service::Service;
+
+use std::collections::HashSet;
+
+use failure::Error;
+use xmltree::{Element, XMLNode};
+
+/// A music service (eg. Spotify or TuneIn) available to the household.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MusicService {
+    /// ID Sonos identifies the service by (eg. 9 for Spotify)
+    pub id: String,
+    pub name: String,
+    /// Version of the Sonos Music API the service implements
+    pub version: String,
+    /// URI of the service's Sonos Music API endpoint
+    pub uri: String,
+    /// Whether the service needs an account to be signed in to before it can be used
+    pub auth_required: bool,
+    /// Whether the service can be used, either because it doesn't need an account or because the
+    /// household has signed in to one
+    pub is_authed: bool,
+}
+
+/// Get the number Sonos uses for a music service in account IDs from the ID of the service (eg.
+/// 2311 for Spotify, which has the ID 9).
+fn service_type(id: &str) -> Option<u32> {
+    id.parse::<u32>().ok().map(|id| id * 256 + 7)
+}
+
+/// Get the child elements of `el` with the given name.
+fn children<'a>(el: &'a Element, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+    el.children
+        .iter()
+        .filter_map(XMLNode::as_element)
+        .filter(move |child| child.name == name)
+}
+
+fn attribute(el: &Element, name: &str) -> String {
+    el.attributes.get(name).cloned().unwrap_or_default()
+}
+
+impl Speaker {
+    /// Get the service types of the accounts the household has signed in to.
+    ///
+    /// This is read from /status/accounts, speakers that don't serve it are treated as not having
+    /// any accounts.
+    async fn account_service_types(&self) -> HashSet<u32> {
+        let accounts = match self.http_get("status/accounts").await {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                debug!("Failed to get accounts of {}: {}", self.ip, e);
+                return HashSet::new();
+            }
+        };
+
+        Element::parse(accounts.as_ref())
+            .ok()
+            .and_then(|root| root.get_child("Accounts").cloned())
+            .map(|accounts| {
+                children(&accounts, "Account")
+                    .filter(|account| account.attributes.get("Deleted").map(String::as_str) != Some("1"))
+                    .filter_map(|account| attribute(account, "Type").parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get every music service available to the household.
+    pub async fn get_music_service_list(&self) -> Result<Vec<MusicService>, Error> {
+        let resp = self.soap(
+            &Service::MUSIC_SERVICES,
+            "ListAvailableServices",
+            "",
+            false,
+        ).await?;
+
+        let services = Element::parse(
+            get_child_element_text(&resp, "AvailableServiceDescriptorList")?.as_bytes(),
+        )?;
+        let accounts = self.account_service_types().await;
+
+        Ok(children(&services, "Service")
+            .map(|service| {
+                let auth_required = service
+                    .get_child("Policy")
+                    .and_then(|policy| policy.attributes.get("Auth"))
+                    .is_some_and(|auth| auth != "Anonymous");
+
+                let id = attribute(service, "Id");
+                let is_authed = !auth_required
+                    || service_type(&id).is_some_and(|service_type| accounts.contains(&service_type));
+
+                MusicService {
+                    name: attribute(service, "Name"),
+                    version: attribute(service, "Version"),
+                    uri: attribute(service, "Uri"),
+                    id,
+                    auth_required,
+                    is_authed,
+                }
+            })
+            .collect())
+    }
+
+    /// Get the ID Sonos identifies the content root of a music service by (eg. SA_RINCON2311_ for
+    /// Spotify), given the name of the service as shown in the Sonos app.
+    ///
+    /// Sonos doesn't serve music service content from the ContentDirectory itself, this is the
+    /// account ID given in the `desc` element of the DIDL-Lite metadata of items from that service.
+    ///
+    /// Returns [SonosError::UnknownMusicService] if no service available to the household has
+    /// that name.
+    pub async fn container_id_for_service(&self, service_name: &str) -> Result<String, Error> {
+        let service = self
+            .get_music_service_list()
+            .await?
+            .into_iter()
+            .find(|service| service.name.eq_ignore_ascii_case(service_name))
+            .ok_or_else(|| SonosError::UnknownMusicService(service_name.to_string()))?;
+
+        let service_type = service_type(&service.id)
+            .ok_or_else(|| SonosError::ParseError(format!("invalid music service id {}", service.id)))?;
+
+        Ok(format!("SA_RINCON{}_", service_type))
+    }
+}
@@ -0,0 +1,92 @@
+use crate::device::{get_child_element_text, get_child_text, xml_escape, Queue, QueuePolicy, Speaker};
+use crate::service::Service;
+
+use failure::Error;
+use xmltree::Element;
+
+/// A playlist saved in the Sonos app, stored on the speakers rather than a music service.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SonosPlaylist {
+    /// ObjectID of the playlist (eg. SQ:12)
+    pub id: String,
+    pub title: String,
+    pub uri: String,
+    /// Number of tracks in the playlist, if the speaker reported it
+    pub track_count: Option<u32>,
+}
+
+fn parse_playlist(el: &Element) -> SonosPlaylist {
+    SonosPlaylist {
+        id: el.attributes.get("id").cloned().unwrap_or_default(),
+        title: get_child_text(el, "title").unwrap_or_default(),
+        uri: get_child_text(el, "res").unwrap_or_default(),
+        track_count: el.attributes.get("childCount").and_then(|count| count.parse().ok()),
+    }
+}
+
+impl Speaker {
+    /// Get a Sonos playlist by its ID (eg. SQ:12).
+    pub async fn sonos_playlist(&self, id: &str) -> Result<SonosPlaylist, Error> {
+        Ok(parse_playlist(&self.browse_metadata(id).await?))
+    }
+
+    /// Insert the tracks of a Sonos playlist (eg. SQ:12) into the queue starting at `position`
+    /// (beginning at 1), leaving the rest of the queue as it was. Returns the number of tracks
+    /// added.
+    ///
+    /// A `position` past the end of the queue adds the tracks to the end.
+    pub async fn load_playlist_into_queue(&self, id: &str, position: u64) -> Result<u32, Error> {
+        let playlist = self.sonos_playlist(id).await?;
+
+        let resp = self.soap(
+            &Service::AV_TRANSPORT,
+            "AddURIToQueue",
+            &format!(
+                r#"
+                  <InstanceID>0</InstanceID>
+                  <EnqueuedURI>{}</EnqueuedURI>
+                  <EnqueuedURIMetaData></EnqueuedURIMetaData>
+                  <DesiredFirstTrackNumberEnqueued>{}</DesiredFirstTrackNumberEnqueued>
+                  <EnqueueAsNext>0</EnqueueAsNext>"#,
+                xml_escape(&playlist.uri),
+                position
+            ),
+            true,
+        ).await?;
+
+        Ok(get_child_element_text(&resp, "NumTracksAdded")?.parse()?)
+    }
+
+    /// Save the queue as a new Sonos playlist called `name` and start playing it, replacing the
+    /// queue with the saved playlist.
+    ///
+    /// See `Queue::save_as_playlist` to save the queue without affecting playback.
+    pub async fn save_queue(&self, name: &str) -> Result<SonosPlaylist, Error> {
+        let playlist = self.queue().save_as_playlist(name).await?;
+
+        // the URI saves looking it up again, but fall back to the ID if the speaker didn't give one
+        let container = if playlist.uri.is_empty() { &playlist.id } else { &playlist.uri };
+        self.av_transport_play_container(container, QueuePolicy::Replace).await?;
+
+        Ok(playlist)
+    }
+}
+
+impl<'a> Queue<'a> {
+    /// Save the queue as a new Sonos playlist called `name`.
+    pub async fn save_as_playlist(&self, name: &str) -> Result<SonosPlaylist, Error> {
+        let resp = self.speaker.soap(
+            &Service::AV_TRANSPORT,
+            "SaveQueue",
+            &format!(
+                "<InstanceID>0</InstanceID><Title>{}</Title><ObjectID></ObjectID>",
+                xml_escape(name)
+            ),
+            true,
+        ).await?;
+
+        self.speaker
+            .sonos_playlist(&get_child_element_text(&resp, "AssignedObjectID")?)
+            .await
+    }
+}
@@ -0,0 +1,63 @@
+use crate::device::{get_child_text, Speaker};
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use failure::Error;
+use futures::prelude::*;
+use xmltree::Element;
+
+/// ObjectID of the container the speaker keeps recently played items in.
+///
+/// This isn't documented by Sonos and isn't served by every firmware version, speakers that don't
+/// have it will fail the browse with a [crate::SonosError::AVTransportError].
+const RECENTLY_PLAYED_OBJECT_ID: &str = "R:0/2";
+
+/// How many items are requested from the speaker at a time by [Speaker::recently_played].
+const PAGE_SIZE: u32 = 100;
+
+/// Something that was played on the household recently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentItem {
+    pub title: String,
+    pub uri: String,
+    /// ID of the music service the item was played from, taken from the sid parameter of the URI
+    pub service_id: Option<u32>,
+    /// When the item was last played as an RFC 3339 timestamp, if the speaker reported it. With the
+    /// `chrono` feature `RecentItem::last_played_at` gives this parsed.
+    pub last_played: Option<String>,
+}
+
+impl RecentItem {
+    /// When the item was last played, if the speaker reported it as a valid timestamp.
+    #[cfg(feature = "chrono")]
+    pub fn last_played_at(&self) -> Option<DateTime<Utc>> {
+        self.last_played
+            .as_deref()
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+            .map(|date| date.with_timezone(&Utc))
+    }
+}
+
+fn parse_recent_item(el: &Element) -> Result<Option<RecentItem>, Error> {
+    let uri = get_child_text(el, "res").unwrap_or_default();
+    let service_id = uri
+        .split(&['?', '&'][..])
+        .find_map(|param| param.strip_prefix("sid="))
+        .and_then(|sid| sid.parse().ok());
+
+    Ok(Some(RecentItem {
+        title: get_child_text(el, "title").unwrap_or_default(),
+        service_id,
+        uri,
+        last_played: get_child_text(el, "date"),
+    }))
+}
+
+impl Speaker {
+    /// Get the items that have recently been played on the household, most recent first.
+    pub async fn recently_played(&self) -> Result<Vec<RecentItem>, Error> {
+        self.browse_stream(RECENTLY_PLAYED_OBJECT_ID.to_string(), PAGE_SIZE, parse_recent_item)
+            .try_collect()
+            .await
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+
+/// A UPnP service hosted by a speaker.
+///
+/// Every service's control and event paths and URN are derived from the same definition so
+/// they can't get out of sync with each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Service {
+    control_path: &'static str,
+    event_path: &'static str,
+    urn: &'static str,
+}
+
+macro_rules! services {
+    ($($(#[$meta:meta])* $name:ident => ($path:literal, $domain:literal, $service:literal),)*) => {
+        impl Service {
+            $(
+                $(#[$meta])*
+                pub const $name: Service = Service {
+                    control_path: concat!($path, "/Control"),
+                    event_path: concat!($path, "/Event"),
+                    urn: concat!("urn:", $domain, ":service:", $service, ":1"),
+                };
+            )*
+        }
+    };
+}
+
+services! {
+    /// Playback and queue control
+    AV_TRANSPORT => ("MediaRenderer/AVTransport", "schemas-upnp-org", "AVTransport"),
+    /// Volume and EQ of a single speaker
+    RENDERING_CONTROL => ("MediaRenderer/RenderingControl", "schemas-upnp-org", "RenderingControl"),
+    /// Volume of all the speakers in a group
+    GROUP_RENDERING_CONTROL => ("MediaRenderer/GroupRenderingControl", "schemas-upnp-org", "GroupRenderingControl"),
+    CONNECTION_MANAGER => ("MediaRenderer/ConnectionManager", "schemas-upnp-org", "ConnectionManager"),
+    QUEUE => ("MediaRenderer/Queue", "schemas-sonos-com", "Queue"),
+    /// Short clips played over the top of whatever's playing, only on S2 firmware
+    AUDIO_CLIP => ("MediaRenderer/AudioClip", "schemas-sonos-com", "AudioClip"),
+    /// Browsing the music library, favorites, playlists and queues
+    CONTENT_DIRECTORY => ("MediaServer/ContentDirectory", "schemas-upnp-org", "ContentDirectory"),
+    ZONE_GROUP_TOPOLOGY => ("ZoneGroupTopology", "schemas-upnp-org", "ZoneGroupTopology"),
+    ALARM_CLOCK => ("AlarmClock", "schemas-upnp-org", "AlarmClock"),
+    SYSTEM_PROPERTIES => ("SystemProperties", "schemas-upnp-org", "SystemProperties"),
+    DEVICE_PROPERTIES => ("DeviceProperties", "schemas-upnp-org", "DeviceProperties"),
+    MUSIC_SERVICES => ("MusicServices", "schemas-upnp-org", "MusicServices"),
+    AUDIO_IN => ("AudioIn", "schemas-upnp-org", "AudioIn"),
+}
+
+impl Service {
+    /// Path SOAP calls to this service are made to (eg. MediaRenderer/AVTransport/Control)
+    pub fn control_path(&self) -> &'static str {
+        self.control_path
+    }
+
+    /// Path to subscribe to events from this service at (eg. MediaRenderer/AVTransport/Event)
+    pub fn event_path(&self) -> &'static str {
+        self.event_path
+    }
+
+    /// URN identifying this service in SOAP calls (eg.
+    /// urn:schemas-upnp-org:service:AVTransport:1)
+    pub fn urn(&self) -> &'static str {
+        self.urn
+    }
+}
+
+/// Port speakers serve plain HTTP on.
+pub(crate) const HTTP_PORT: u16 = 1400;
+
+/// Port speakers serve HTTPS on, this is required by S2 firmware in some markets.
+pub(crate) const HTTPS_PORT: u16 = 1443;
+
+/// An HTTP endpoint on a speaker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeakerEndpoint {
+    pub ip: IpAddr,
+    pub https: bool,
+    pub port: u16,
+    /// Path relative to the root of the speaker's web server (eg. xml/device_description.xml)
+    pub path: String,
+}
+
+impl SpeakerEndpoint {
+    /// Create a new endpoint for the given path, served over plain HTTP on the default port.
+    pub fn new(ip: IpAddr, path: &str) -> Self {
+        Self {
+            ip,
+            https: false,
+            port: HTTP_PORT,
+            path: path.trim_start_matches('/').to_string(),
+        }
+    }
+
+    /// The full URL of this endpoint.
+    pub fn url(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for SpeakerEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scheme = if self.https { "https" } else { "http" };
+        write!(f, "{}://{}/{}", scheme, SocketAddr::new(self.ip, self.port), self.path)
+    }
+}
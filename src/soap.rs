@@ -0,0 +1,147 @@
+use crate::device::{get_child_element_text, Speaker, SOAP_TIMEOUT};
+use crate::service::Service;
+
+use std::time::Duration;
+
+use failure::Error;
+use xmltree::Element;
+
+/// A value that can be read from an argument of a SOAP response.
+pub(crate) trait SoapValue: Sized {
+    fn parse(text: &str) -> Result<Self, Error>;
+}
+
+macro_rules! parsed_soap_values {
+    ($($ty:ty),*) => {
+        $(
+            impl SoapValue for $ty {
+                fn parse(text: &str) -> Result<Self, Error> {
+                    Ok(text.parse()?)
+                }
+            }
+        )*
+    };
+}
+
+parsed_soap_values!(u8, i8, u16, u32, u64, String);
+
+impl SoapValue for bool {
+    /// Sonos sends booleans as 1 or 0.
+    fn parse(text: &str) -> Result<Self, Error> {
+        Ok(text == "1" || text.eq_ignore_ascii_case("true"))
+    }
+}
+
+/// Read the argument with the given name from the `{action}Response` element of a SOAP response.
+pub(crate) fn argument<T: SoapValue>(resp: &Element, name: &str) -> Result<T, Error> {
+    T::parse(&get_child_element_text(resp, name)?)
+}
+
+/// The arguments of the `{action}Response` element of a SOAP response, read into a struct.
+pub(crate) trait SoapResponse: Sized {
+    fn from_response(resp: &Element) -> Result<Self, Error>;
+}
+
+/// Declare a struct holding the arguments of a SOAP response, each field being read from the
+/// argument it's mapped to.
+macro_rules! soap_response {
+    ($(#[$meta:meta])* struct $name:ident { $($field:ident: $ty:ty = $argument:literal,)* }) => {
+        $(#[$meta])*
+        pub(crate) struct $name {
+            $(pub(crate) $field: $ty,)*
+        }
+
+        impl crate::soap::SoapResponse for $name {
+            fn from_response(resp: &xmltree::Element) -> Result<Self, failure::Error> {
+                Ok(Self {
+                    $($field: crate::soap::argument(resp, $argument)?,)*
+                })
+            }
+        }
+    };
+}
+
+pub(crate) use soap_response;
+
+/// A SOAP call to make against a speaker, built up one argument at a time.
+#[derive(Debug, Clone, Default)]
+pub struct SoapRequest<'a> {
+    endpoint: &'a str,
+    service: &'a str,
+    action: &'a str,
+    payload: &'a str,
+    use_coordinator: bool,
+    timeout: Option<Duration>,
+}
+
+impl<'a> SoapRequest<'a> {
+    /// Create an empty request, the endpoint, service and action need to be set before it's sent.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a request to one of the speaker's services, setting the endpoint and service.
+    pub fn for_service(service: &Service) -> Self {
+        Self::new()
+            .endpoint(service.control_path())
+            .service(service.urn())
+    }
+
+    /// The SOAP endpoint to call (eg. MediaRenderer/AVTransport/Control)
+    pub fn endpoint(mut self, endpoint: &'a str) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// The SOAP service to call (eg. urn:schemas-upnp-org:service:AVTransport:1)
+    pub fn service(mut self, service: &'a str) -> Self {
+        self.service = service;
+        self
+    }
+
+    /// The action to call on the service (eg. Play)
+    pub fn action(mut self, action: &'a str) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// XML doc to pass inside the action call body, empty by default
+    pub fn payload(mut self, payload: &'a str) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// Whether the call should be made against the coordinator of the speaker's group rather
+    /// than the speaker itself, false by default
+    pub fn use_coordinator(mut self, use_coordinator: bool) -> Self {
+        self.use_coordinator = use_coordinator;
+        self
+    }
+
+    /// How long to wait for the speaker to respond before giving up with a
+    /// [crate::SonosError::Timeout], 10 seconds by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Make the call against `speaker` (or its coordinator), returning the `{action}Response`
+    /// element.
+    pub async fn send(&self, speaker: &Speaker) -> Result<Element, Error> {
+        speaker
+            .call_soap(
+                self.endpoint,
+                self.service,
+                self.action,
+                self.payload,
+                self.use_coordinator,
+                self.timeout.unwrap_or(SOAP_TIMEOUT),
+            )
+            .await
+    }
+
+    /// Make the call against `speaker` (or its coordinator), reading the response into `T`.
+    pub(crate) async fn send_typed<T: SoapResponse>(&self, speaker: &Speaker) -> Result<T, Error> {
+        T::from_response(&self.send(speaker).await?)
+    }
+}
@@ -0,0 +1,257 @@
+use crate::device::Speaker;
+use crate::service::Service;
+use crate::soap::soap_response;
+
+use std::time::Duration;
+
+use failure::Error;
+
+/// The longest audio delay Sonos allows, see [Speaker::set_audio_delay].
+const MAX_AUDIO_DELAY: Duration = Duration::from_millis(200);
+
+/// Path to the Service Control Point Description of the RenderingControl service.
+const RENDERING_CONTROL_SCPD: &str = "/xml/RenderingControl1.xml";
+
+soap_response! {
+    struct GetBassResponse {
+        current_bass: i8 = "CurrentBass",
+    }
+}
+
+soap_response! {
+    struct GetTrebleResponse {
+        current_treble: i8 = "CurrentTreble",
+    }
+}
+
+soap_response! {
+    struct GetEQResponse {
+        current_value: u32 = "CurrentValue",
+    }
+}
+
+soap_response! {
+    struct GetLoudnessResponse {
+        current_loudness: bool = "CurrentLoudness",
+    }
+}
+
+/// The audio settings of a speaker, as read by [Speaker::sound_settings].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoundSettings {
+    /// Volume from 0-100
+    pub volume: u8,
+    pub muted: bool,
+    /// Bass from -10 to 10
+    pub bass: i8,
+    /// Treble from -10 to 10
+    pub treble: i8,
+    /// Whether the speaker boosts bass and treble at low volumes
+    pub loudness: bool,
+}
+
+/// Whether a speaker can adjust its bass and by how much, as read by
+/// [Speaker::get_bass_capabilities].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BassCapabilities {
+    pub supported: bool,
+    /// Lowest bass level [Speaker::set_bass] accepts
+    pub min: i8,
+    /// Highest bass level [Speaker::set_bass] accepts
+    pub max: i8,
+}
+
+impl Speaker {
+    /// Check if the speaker can adjust its bass and what range of levels it accepts.
+    ///
+    /// This is read from the RenderingControl service description rather than worked out from
+    /// the model, falling back to -10 to 10 if the speaker doesn't give a range.
+    pub async fn get_bass_capabilities(&self) -> Result<BassCapabilities, Error> {
+        let bass = self
+            .get_state_variable_list(RENDERING_CONTROL_SCPD)
+            .await?
+            .into_iter()
+            .find(|variable| variable.name == "Bass");
+
+        Ok(match bass {
+            Some(bass) => BassCapabilities {
+                supported: true,
+                min: bass.minimum.map_or(Ok(-10), |min| min.parse())?,
+                max: bass.maximum.map_or(Ok(10), |max| max.parse())?,
+            },
+            None => BassCapabilities { supported: false, min: 0, max: 0 },
+        })
+    }
+
+    /// Get the bass level from -10 to 10.
+    pub async fn bass(&self) -> Result<i8, Error> {
+        let resp: GetBassResponse = self.soap_typed(
+            &Service::RENDERING_CONTROL,
+            "GetBass",
+            "<InstanceID>0</InstanceID>",
+            false,
+        ).await?;
+
+        Ok(resp.current_bass)
+    }
+
+    /// Set the bass level from -10 to 10.
+    pub async fn set_bass(&self, bass: i8) -> Result<(), Error> {
+        if !(-10..=10).contains(&bass) {
+            panic!("Bass must be between -10 and 10, got {}.", bass);
+        }
+
+        self.soap(
+            &Service::RENDERING_CONTROL,
+            "SetBass",
+            &format!("<InstanceID>0</InstanceID><DesiredBass>{}</DesiredBass>", bass),
+            false,
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Get the treble level from -10 to 10.
+    pub async fn treble(&self) -> Result<i8, Error> {
+        let resp: GetTrebleResponse = self.soap_typed(
+            &Service::RENDERING_CONTROL,
+            "GetTreble",
+            "<InstanceID>0</InstanceID>",
+            false,
+        ).await?;
+
+        Ok(resp.current_treble)
+    }
+
+    /// Set the treble level from -10 to 10.
+    pub async fn set_treble(&self, treble: i8) -> Result<(), Error> {
+        if !(-10..=10).contains(&treble) {
+            panic!("Treble must be between -10 and 10, got {}.", treble);
+        }
+
+        self.soap(
+            &Service::RENDERING_CONTROL,
+            "SetTreble",
+            &format!("<InstanceID>0</InstanceID><DesiredTreble>{}</DesiredTreble>", treble),
+            false,
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Check if loudness is turned on.
+    pub async fn loudness(&self) -> Result<bool, Error> {
+        let resp: GetLoudnessResponse = self.soap_typed(
+            &Service::RENDERING_CONTROL,
+            "GetLoudness",
+            "<InstanceID>0</InstanceID><Channel>Master</Channel>",
+            false,
+        ).await?;
+
+        Ok(resp.current_loudness)
+    }
+
+    /// Turn loudness on or off.
+    pub async fn set_loudness(&self, loudness: bool) -> Result<(), Error> {
+        self.soap(
+            &Service::RENDERING_CONTROL,
+            "SetLoudness",
+            &format!(
+                "<InstanceID>0</InstanceID><Channel>Master</Channel><DesiredLoudness>{}</DesiredLoudness>",
+                loudness as u8
+            ),
+            false,
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Reset the speaker's EQ to a flat response: a bass and treble of 0 with loudness turned
+    /// off.
+    pub async fn reset_eq(&self) -> Result<(), Error> {
+        futures::try_join!(
+            self.set_bass(0),
+            self.set_treble(0),
+            self.set_loudness(false),
+        )?;
+
+        Ok(())
+    }
+
+    /// Get the value of one of the EQ settings that don't have their own action (eg. AudioDelay).
+    async fn eq(&self, eq_type: &str) -> Result<u32, Error> {
+        let resp: GetEQResponse = self.soap_typed(
+            &Service::RENDERING_CONTROL,
+            "GetEQ",
+            &format!("<InstanceID>0</InstanceID><EQType>{}</EQType>", eq_type),
+            false,
+        ).await?;
+
+        Ok(resp.current_value)
+    }
+
+    /// Set one of the EQ settings that don't have their own action.
+    async fn set_eq(&self, eq_type: &str, value: u32) -> Result<(), Error> {
+        self.soap(
+            &Service::RENDERING_CONTROL,
+            "SetEQ",
+            &format!(
+                "<InstanceID>0</InstanceID><EQType>{}</EQType><DesiredValue>{}</DesiredValue>",
+                eq_type, value
+            ),
+            false,
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Get how far audio is delayed behind the TV picture for lip sync.
+    ///
+    /// Only home theatre speakers support this, others return an error.
+    pub async fn get_audio_delay(&self) -> Result<Duration, Error> {
+        Ok(Duration::from_millis(u64::from(self.eq("AudioDelay").await?)))
+    }
+
+    /// Set how far audio is delayed behind the TV picture for lip sync, from 0 to 200ms.
+    pub async fn set_audio_delay(&self, delay: Duration) -> Result<(), Error> {
+        if delay > MAX_AUDIO_DELAY {
+            panic!("Audio delay must be at most 200ms, got {}ms.", delay.as_millis());
+        }
+
+        self.set_eq("AudioDelay", delay.as_millis() as u32).await
+    }
+
+    /// Get the volume, mute, bass, treble and loudness of the speaker at once.
+    pub async fn sound_settings(&self) -> Result<SoundSettings, Error> {
+        let (volume, muted, bass, treble, loudness) = futures::try_join!(
+            self.volume(),
+            self.muted(),
+            self.bass(),
+            self.treble(),
+            self.loudness(),
+        )?;
+
+        Ok(SoundSettings { volume, muted, bass, treble, loudness })
+    }
+
+    /// Set the volume, mute, bass, treble and loudness of the speaker at once.
+    pub async fn apply_sound_settings(&self, settings: &SoundSettings) -> Result<(), Error> {
+        let mute = async {
+            if settings.muted {
+                self.mute().await
+            } else {
+                self.unmute().await
+            }
+        };
+
+        futures::try_join!(
+            self.set_volume(settings.volume),
+            mute,
+            self.set_bass(settings.bass),
+            self.set_treble(settings.treble),
+            self.set_loudness(settings.loudness),
+        )?;
+
+        Ok(())
+    }
+}
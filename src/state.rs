@@ -0,0 +1,156 @@
+use crate::device::{get_child_text, Speaker, TransportState};
+#[cfg(feature = "events")]
+use crate::event_server::EventServer;
+#[cfg(feature = "events")]
+use crate::events::{EventReceiver, TransportEvent, VolumeEvent};
+
+#[cfg(feature = "events")]
+use std::sync::Arc;
+use std::time::Duration;
+
+use failure::Error;
+#[cfg(feature = "events")]
+use futures::future::{self, Either};
+use tokio::sync::watch;
+#[cfg(feature = "events")]
+use tokio::sync::broadcast::error::RecvError;
+
+/// How often [Speaker::state_watch] polls the speaker for changes.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A snapshot of what a speaker is doing, as tracked by [Speaker::state_watch].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeakerState {
+    pub transport_state: TransportState,
+    pub volume: u8,
+    pub muted: bool,
+    /// URI of the track currently loaded, if any
+    pub track_uri: Option<String>,
+}
+
+impl Speaker {
+    /// Fetch the current state of the speaker.
+    async fn fetch_state(&self) -> Result<SpeakerState, Error> {
+        let position = self.get_position_info_raw().await?;
+
+        Ok(SpeakerState {
+            transport_state: self.transport_state().await?,
+            volume: self.volume().await?,
+            muted: self.muted().await?,
+            track_uri: get_child_text(&position, "TrackURI").filter(|uri| !uri.is_empty()),
+        })
+    }
+
+    /// Keep track of the speaker's state by polling it every 5 seconds.
+    ///
+    /// See [Speaker::state_watch_with_interval].
+    pub async fn state_watch(&self) -> Result<watch::Receiver<SpeakerState>, Error> {
+        self.state_watch_with_interval(DEFAULT_POLL_INTERVAL).await
+    }
+
+    /// Keep track of the speaker's state by polling it every `interval`.
+    ///
+    /// The returned receiver always holds the latest known state, and is only notified when the
+    /// state actually changes. Polling stops once every receiver has been dropped. Polls that fail
+    /// (eg. because the speaker is briefly unreachable) are skipped.
+    ///
+    /// This must be called from within a tokio runtime.
+    pub async fn state_watch_with_interval(
+        &self,
+        interval: Duration,
+    ) -> Result<watch::Receiver<SpeakerState>, Error> {
+        let (sender, receiver) = watch::channel(self.fetch_state().await?);
+        let speaker = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            // the first tick completes straight away, and we've only just fetched the state
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+
+                if sender.is_closed() {
+                    return;
+                }
+
+                match speaker.fetch_state().await {
+                    Ok(state) => {
+                        sender.send_if_modified(|current| {
+                            let changed = *current != state;
+                            *current = state;
+                            changed
+                        });
+                    }
+                    Err(e) => debug!("Failed to poll state of {}: {}", speaker.ip, e),
+                }
+            }
+        });
+
+        Ok(receiver)
+    }
+
+    /// Keep track of the speaker's state using events received by `server` rather than polling.
+    ///
+    /// The returned receiver always holds the latest known state, the subscriptions to the
+    /// speaker are kept alive until every receiver has been dropped.
+    #[cfg(feature = "events")]
+    pub async fn state_watch_from_events(
+        &self,
+        server: &EventServer,
+    ) -> Result<watch::Receiver<SpeakerState>, Error> {
+        let transport = self.subscribe_to_transport_events(server).await?;
+        let volume = self.subscribe_to_volume_events(server).await?;
+        let (sender, receiver) = watch::channel(self.fetch_state().await?);
+        let sender = Arc::new(sender);
+
+        tokio::spawn(forward_events(transport, sender.clone(), |state, event: TransportEvent| {
+            if let Some(transport_state) = event.transport_state {
+                state.transport_state = transport_state;
+            }
+
+            if let Some(track_uri) = event.track_uri {
+                state.track_uri = Some(track_uri).filter(|uri| !uri.is_empty());
+            }
+        }));
+
+        tokio::spawn(forward_events(volume, sender, |state, event: VolumeEvent| {
+            if let Some(volume) = event.volume.get("Master") {
+                state.volume = *volume;
+            }
+
+            if let Some(muted) = event.mute {
+                state.muted = muted;
+            }
+        }));
+
+        Ok(receiver)
+    }
+}
+
+/// Apply every event from `events` to the state held by `sender` until every receiver of the
+/// state has been dropped.
+#[cfg(feature = "events")]
+async fn forward_events<T, F>(
+    mut events: EventReceiver<T>,
+    sender: Arc<watch::Sender<SpeakerState>>,
+    apply: F,
+) where
+    T: Clone,
+    F: Fn(&mut SpeakerState, T),
+{
+    loop {
+        let event = match future::select(Box::pin(sender.closed()), Box::pin(events.recv())).await {
+            Either::Left(_) => return,
+            Either::Right((Ok(event), _)) => event,
+            Either::Right((Err(RecvError::Lagged(_)), _)) => continue,
+            Either::Right((Err(RecvError::Closed), _)) => return,
+        };
+
+        sender.send_if_modified(|state| {
+            let before = state.clone();
+            apply(state, event);
+            *state != before
+        });
+    }
+}
@@ -0,0 +1,209 @@
+use crate::device::Speaker;
+use crate::error::*;
+use crate::gena::SubscriptionId;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use failure::Error;
+use tokio::time::{self, Instant};
+
+/// How long subscriptions are requested for unless the manager was told otherwise.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1800);
+
+/// How often the background task looks for subscriptions that are due for renewal.
+const RENEWAL_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+struct Registration {
+    speaker: Speaker,
+    endpoint: String,
+    callback_url: String,
+    /// The subscription on the speaker, `None` if the last attempt to (re)subscribe failed
+    sid: Option<SubscriptionId>,
+    renew_at: Instant,
+}
+
+struct Inner {
+    registrations: Mutex<HashMap<u64, Registration>>,
+    next_id: AtomicU64,
+    timeout: Duration,
+}
+
+/// Keeps track of GENA event subscriptions and renews them before they expire.
+///
+/// Subscriptions are renewed halfway through the timeout granted by the speaker. If a renewal
+/// fails (eg. because the speaker rebooted and forgot about it) a fresh subscription is made to
+/// the same callback URL.
+///
+/// All clones of a manager share the same registry and a single background task, which is spawned
+/// onto the current tokio runtime when the manager is created.
+#[derive(Clone)]
+pub struct SubscriptionManager {
+    inner: Arc<Inner>,
+}
+
+/// A live GENA subscription, created by [SubscriptionManager::subscribe].
+///
+/// The subscription is kept alive for as long as this handle exists, dropping it sends an
+/// `UNSUBSCRIBE` to the speaker.
+pub struct EventSubscription {
+    id: u64,
+    inner: Arc<Inner>,
+}
+
+impl SubscriptionManager {
+    /// Create a new manager requesting the default timeout of 1800 seconds for subscriptions.
+    ///
+    /// This must be called from within a tokio runtime.
+    pub fn new() -> Self {
+        Self::with_timeout(DEFAULT_TIMEOUT)
+    }
+
+    /// Create a new manager requesting the given timeout for subscriptions.
+    ///
+    /// This must be called from within a tokio runtime.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        let inner = Arc::new(Inner {
+            registrations: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+            timeout,
+        });
+
+        tokio::spawn(run(Arc::downgrade(&inner)));
+
+        Self { inner }
+    }
+
+    /// Subscribe to events from the given endpoint on a speaker (eg.
+    /// MediaRenderer/AVTransport/Event), asking for them to be sent to `callback_url`.
+    pub async fn subscribe(
+        &self,
+        speaker: &Speaker,
+        endpoint: &str,
+        callback_url: &str,
+    ) -> Result<EventSubscription, Error> {
+        let (sid, granted) = speaker
+            .subscribe_for(endpoint, callback_url, self.inner.timeout.as_secs() as u32)
+            .await?;
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+
+        self.inner.registrations.lock().unwrap().insert(id, Registration {
+            speaker: speaker.clone(),
+            endpoint: endpoint.to_string(),
+            callback_url: callback_url.to_string(),
+            sid: Some(sid),
+            renew_at: Instant::now() + granted / 2,
+        });
+
+        Ok(EventSubscription {
+            id,
+            inner: self.inner.clone(),
+        })
+    }
+}
+
+impl Default for SubscriptionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventSubscription {
+    /// The current SID of this subscription.
+    ///
+    /// This changes if the subscription has to be recreated and is `None` while the speaker can't
+    /// be reached.
+    pub fn sid(&self) -> Option<String> {
+        self.inner
+            .registrations
+            .lock()
+            .unwrap()
+            .get(&self.id)
+            .and_then(|r| r.sid.as_ref().map(|sid| sid.sid().to_string()))
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        let registration = self.inner.registrations.lock().unwrap().remove(&self.id);
+
+        if let Some(Registration { speaker, sid: Some(sid), .. }) = registration {
+            // we can't wait for the speaker in drop, so send the UNSUBSCRIBE in the background
+            // if we're still inside a runtime. otherwise the subscription will just expire.
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                handle.spawn(async move {
+                    if let Err(e) = speaker.unsubscribe(&sid).await {
+                        debug!("Failed to unsubscribe {} from {}: {}", sid, sid.endpoint(), e);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Renews any subscriptions that are due until every handle to the manager has been dropped.
+async fn run(inner: Weak<Inner>) {
+    let mut interval = time::interval(RENEWAL_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let inner = match inner.upgrade() {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        let now = Instant::now();
+        let due = inner
+            .registrations
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, r)| r.renew_at <= now)
+            .map(|(id, r)| (*id, r.speaker.clone(), r.endpoint.clone(), r.callback_url.clone(), r.sid.clone()))
+            .collect::<Vec<_>>();
+
+        let timeout_secs = inner.timeout.as_secs() as u32;
+
+        for (id, speaker, endpoint, callback_url, sid) in due {
+            let renewed = match &sid {
+                Some(sid) => speaker
+                    .renew_subscription(sid, timeout_secs)
+                    .await
+                    .map(|granted| (sid.clone(), granted)),
+                None => Err(SonosError::DeviceUnreachable.into()),
+            };
+
+            let result = match renewed {
+                Ok(renewed) => Ok(renewed),
+                Err(e) => {
+                    debug!("Couldn't renew subscription to {} ({}), resubscribing", endpoint, e);
+                    speaker.subscribe_for(&endpoint, &callback_url, timeout_secs).await
+                }
+            };
+
+            let mut registrations = inner.registrations.lock().unwrap();
+
+            match (registrations.get_mut(&id), result) {
+                (Some(registration), Ok((sid, granted))) => {
+                    registration.sid = Some(sid);
+                    registration.renew_at = Instant::now() + granted / 2;
+                }
+                (Some(registration), Err(e)) => {
+                    error!("Failed to resubscribe to {} on {}: {}", endpoint, speaker.ip, e);
+                    registration.sid = None;
+                    registration.renew_at = Instant::now();
+                }
+                (None, Ok((sid, _))) => {
+                    // the subscription was dropped while we were renewing it
+                    tokio::spawn(async move {
+                        let _ = speaker.unsubscribe(&sid).await;
+                    });
+                }
+                (None, Err(_)) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::device::{get_child_element, get_child_element_text, Speaker};
+use crate::error::*;
+use crate::model::{BOOST_MODEL_NUMBER, BRIDGE_MODEL_NUMBER};
+use crate::service::Service;
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use failure::Error;
+use futures::prelude::*;
+use regex::Regex;
+use xmltree::{Element, XMLNode};
+
+lazy_static! {
+    static ref LOCATION_REGEX: Regex = Regex::new(r"^https?://(.+?):\d+/")
+        .expect("Failed to create regex");
+
+    /// Coordinators looked up by [Speaker::coordinator_speaker], keyed by the UUID of the speaker
+    /// they were looked up for.
+    static ref COORDINATOR_CACHE: Mutex<HashMap<String, (Instant, Speaker)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// How many device descriptions [Speaker::group_members] fetches at once.
+const MAX_CONCURRENT_DESCRIPTIONS: usize = 4;
+
+/// How long a coordinator returned by [Speaker::coordinator_speaker] is reused for.
+const COORDINATOR_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// A group of speakers playing in sync, led by a single coordinator.
+#[derive(Debug, Clone)]
+pub struct ZoneGroup {
+    /// Identifier of this group (eg. RINCON_000E58000000001400:57)
+    pub id: String,
+    /// UUID of the speaker coordinating playback for this group
+    pub coordinator: String,
+    pub members: Vec<ZoneMember>,
+}
+
+/// A speaker that's a member of a [ZoneGroup].
+#[derive(Debug, Clone)]
+pub struct ZoneMember {
+    pub uuid: String,
+    pub ip: IpAddr,
+    pub name: String,
+    pub is_coordinator: bool,
+}
+
+/// The role a device plays in the household.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeakerType {
+    /// A speaker that plays audio on its own
+    ZonePlayer,
+    /// A surround or sub bonded to another speaker, which can't be controlled on its own
+    Satellite,
+    /// A Sonos Bridge, which only connects other devices to the network
+    Bridge,
+    /// A Sonos Boost, which only connects other devices to the network
+    Boost,
+}
+
+impl ZoneGroup {
+    /// Check if the speaker with the given UUID is a member of this group.
+    pub fn contains(&self, uuid: &str) -> bool {
+        self.members.iter().any(|m| m.uuid == uuid)
+    }
+
+    /// Get the member coordinating this group.
+    pub fn coordinator_member(&self) -> Option<&ZoneMember> {
+        self.members.iter().find(|m| m.is_coordinator)
+    }
+}
+
+impl ZoneMember {
+    /// Fetch the full [Speaker] for this member from its device description.
+    ///
+    /// Members only carry what the topology says about them, as making every member a [Speaker]
+    /// up front (eg. through `Deref<Target = Speaker>`) would mean fetching the device
+    /// description of every speaker in the household whenever the topology is read.
+    pub async fn to_speaker(&self) -> Result<Speaker, Error> {
+        Speaker::from_ip(self.ip).await
+    }
+}
+
+fn get_attribute<'a>(el: &'a Element, name: &str) -> Result<&'a str, Error> {
+    el.attributes
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| SonosError::ParseError(format!("missing {} attribute on {}", name, el.name)).into())
+}
+
+fn parse_member(el: &Element, coordinator: &str) -> Result<ZoneMember, Error> {
+    let uuid = get_attribute(el, "UUID")?;
+    let location = get_attribute(el, "Location")?;
+
+    Ok(ZoneMember {
+        uuid: uuid.to_string(),
+        ip: LOCATION_REGEX
+            .captures(location)
+            .ok_or_else(|| SonosError::ParseError("couldn't parse member location".to_string()))?[1]
+            .parse()?,
+        name: get_attribute(el, "ZoneName")?.to_string(),
+        is_coordinator: uuid == coordinator,
+    })
+}
+
+/// Find the ZoneGroupMember or Satellite element describing the device with the given UUID.
+fn find_device<'a>(el: &'a Element, uuid: &str) -> Option<&'a Element> {
+    el.children
+        .iter()
+        .filter_map(XMLNode::as_element)
+        .find_map(|child| match child.name.as_str() {
+            "ZoneGroupMember" | "Satellite" if child.attributes.get("UUID").map(String::as_str) == Some(uuid) => {
+                Some(child)
+            }
+            _ => find_device(child, uuid),
+        })
+}
+
+/// Parse the XML document contained in a GetZoneGroupState response.
+pub(crate) fn parse_zone_group_state(xml: &str) -> Result<Vec<ZoneGroup>, Error> {
+    let root = Element::parse(xml.as_bytes())?;
+
+    // older firmware returns the ZoneGroups element as the root of the document
+    let zone_groups = if root.name == "ZoneGroups" {
+        &root
+    } else {
+        get_child_element(&root, "ZoneGroups")?
+    };
+
+    zone_groups
+        .children
+        .iter()
+        .filter_map(XMLNode::as_element)
+        .filter(|el| el.name == "ZoneGroup")
+        .map(|group| {
+            let coordinator = get_attribute(group, "Coordinator")?;
+
+            Ok(ZoneGroup {
+                id: get_attribute(group, "ID")?.to_string(),
+                coordinator: coordinator.to_string(),
+                members: group
+                    .children
+                    .iter()
+                    .filter_map(XMLNode::as_element)
+                    .filter(|el| el.name == "ZoneGroupMember")
+                    .map(|member| parse_member(member, coordinator))
+                    .collect::<Result<_, Error>>()?,
+            })
+        })
+        .collect()
+}
+
+impl Speaker {
+    /// Get the XML document describing the topology of the household, as returned by
+    /// GetZoneGroupState.
+    ///
+    /// This is mostly useful for debugging, see [Speaker::zone_group_state] for the parsed groups.
+    pub async fn raw_topology(&self) -> Result<String, Error> {
+        let resp = self.soap(
+            &Service::ZONE_GROUP_TOPOLOGY,
+            "GetZoneGroupState",
+            "",
+            false,
+        ).await?;
+
+        Ok(get_child_element_text(&resp, "ZoneGroupState")?.into_owned())
+    }
+
+    /// Get every group in the household this speaker belongs to.
+    pub async fn zone_group_state(&self) -> Result<Vec<ZoneGroup>, Error> {
+        parse_zone_group_state(&self.raw_topology().await?)
+    }
+
+    /// Get the role this device plays in the household, so devices that can't play audio on
+    /// their own can be told apart from speakers.
+    ///
+    /// This is read from the household's topology, which is where Sonos exposes whether a device
+    /// is bonded as a satellite or acting as a bridge.
+    pub async fn get_speaker_type(&self) -> Result<SpeakerType, Error> {
+        let root = Element::parse(self.raw_topology().await?.as_bytes())?;
+        let device = find_device(&root, &self.uuid)
+            .ok_or_else(|| SonosError::DeviceNotFound(self.uuid.to_string()))?;
+
+        Ok(if device.name == "Satellite" {
+            SpeakerType::Satellite
+        } else if self.model_number == BOOST_MODEL_NUMBER {
+            SpeakerType::Boost
+        } else if self.model_number == BRIDGE_MODEL_NUMBER
+            || device.attributes.get("IsZoneBridge").map(String::as_str) == Some("1")
+        {
+            SpeakerType::Bridge
+        } else {
+            SpeakerType::ZonePlayer
+        })
+    }
+
+    /// Get the group this speaker is in.
+    pub(crate) async fn zone_group(&self) -> Result<ZoneGroup, Error> {
+        self.zone_group_state()
+            .await?
+            .into_iter()
+            .find(|g| g.contains(&self.uuid))
+            .ok_or_else(|| SonosError::DeviceNotFound(self.uuid.to_string()).into())
+    }
+
+    /// Check if this speaker is the coordinator of the group it's in.
+    pub async fn is_coordinator(&self) -> Result<bool, Error> {
+        Ok(self.zone_group().await?.coordinator == self.uuid)
+    }
+
+    /// Get every member of the group this speaker is in, including this speaker.
+    pub async fn get_all_zone_members(&self) -> Result<Vec<ZoneMember>, Error> {
+        Ok(self.zone_group().await?.members)
+    }
+
+    /// Find the speaker with the given UUID anywhere in the household, such as one named in an
+    /// event, without having to know its IP.
+    ///
+    /// Use [ZoneMember::to_speaker] to get the full [Speaker] for it.
+    pub async fn query_topology_for_uuid(&self, uuid: &str) -> Result<ZoneMember, Error> {
+        self.zone_group_state()
+            .await?
+            .into_iter()
+            .flat_map(|group| group.members)
+            .find(|member| member.uuid == uuid)
+            .ok_or_else(|| SonosError::DeviceNotFound(uuid.to_string()).into())
+    }
+
+    /// Get every member of the group this speaker is in as a fully-populated [Speaker], including
+    /// this speaker and the coordinator.
+    ///
+    /// The topology is only fetched once, and the device descriptions of the other members are
+    /// fetched a few at a time rather than one after another.
+    pub async fn group_members(&self) -> Result<Vec<Speaker>, Error> {
+        stream::iter(self.zone_group().await?.members)
+            .map(|member| async move {
+                if member.uuid == self.uuid {
+                    Ok(self.clone())
+                } else {
+                    member.to_speaker().await
+                }
+            })
+            .buffered(MAX_CONCURRENT_DESCRIPTIONS)
+            .try_collect()
+            .await
+    }
+
+    /// Get the identifier of the group this speaker is in (eg. RINCON_000E58000000001400:57).
+    ///
+    /// Two speakers are in the same group if and only if they have the same group ID.
+    pub async fn current_group_id(&self) -> Result<String, Error> {
+        let resp = self.soap(
+            &Service::ZONE_GROUP_TOPOLOGY,
+            "GetZoneGroupAttributes",
+            "",
+            false,
+        ).await?;
+
+        Ok(get_child_element_text(&resp, "CurrentZoneGroupID")?.into_owned())
+    }
+
+    /// Check if this speaker is in the same group as `other`.
+    ///
+    /// Unlike comparing [Speaker::current_group_id] of both speakers, this only fetches the
+    /// topology once.
+    pub async fn is_in_same_group_as(&self, other: &Speaker) -> Result<bool, Error> {
+        Ok(self.zone_group().await?.contains(&other.uuid))
+    }
+
+    /// Get the coordinator of the group this speaker is in as a fully-populated [Speaker].
+    ///
+    /// The result is cached for 30 seconds so repeated calls don't refetch the topology and device
+    /// description every time.
+    pub async fn coordinator_speaker(&self) -> Result<Speaker, Error> {
+        if let Some((fetched, speaker)) = COORDINATOR_CACHE.lock().unwrap().get(&self.uuid) {
+            if fetched.elapsed() < COORDINATOR_CACHE_TTL {
+                return Ok(speaker.clone());
+            }
+        }
+
+        let groups = self.zone_group_state().await?;
+        let coordinator = groups
+            .iter()
+            .find(|g| g.contains(&self.uuid))
+            .and_then(ZoneGroup::coordinator_member)
+            .ok_or_else(|| SonosError::DeviceNotFound(self.uuid.to_string()))?;
+
+        let speaker = if coordinator.uuid == self.uuid {
+            self.clone()
+        } else {
+            Speaker::from_ip(coordinator.ip).await?
+        };
+
+        COORDINATOR_CACHE
+            .lock()
+            .unwrap()
+            .insert(self.uuid.clone(), (Instant::now(), speaker.clone()));
+
+        Ok(speaker)
+    }
+}
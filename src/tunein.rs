@@ -0,0 +1,68 @@
+use crate::device::{tunein_station, Speaker, HTTP_CLIENT};
+use crate::error::*;
+
+use failure::Error;
+use xmltree::{Element, XMLNode};
+
+/// TuneIn's OPML search endpoint, which is what the Sonos app searches stations through.
+const TUNEIN_SEARCH_URL: &str = "https://opml.radiotime.com/Search.ashx";
+
+/// A TuneIn radio station found by [Speaker::tunein_search].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadioStation {
+    /// TuneIn ID of the station (eg. s24940), as taken by [Speaker::play_tunein_station]
+    pub id: String,
+    pub title: String,
+    /// Stream URI Sonos plays the station from (eg. x-sonosapi-stream:s24940?sid=254)
+    pub uri: String,
+    pub logo_uri: Option<String>,
+}
+
+/// Collect every station among the `outline` elements of an OPML document, which TuneIn nests
+/// when it groups results.
+fn collect_stations(el: &Element, stations: &mut Vec<RadioStation>) {
+    for outline in el.children.iter().filter_map(XMLNode::as_element).filter(|el| el.name == "outline") {
+        let id = outline.attributes.get("guide_id").filter(|id| id.starts_with('s'));
+
+        match id {
+            Some(id) if outline.attributes.get("type").map(String::as_str) == Some("audio") => {
+                stations.push(RadioStation {
+                    id: id.clone(),
+                    title: outline.attributes.get("text").cloned().unwrap_or_default(),
+                    uri: tunein_station(id).0,
+                    logo_uri: outline.attributes.get("image").cloned(),
+                });
+            }
+            _ => collect_stations(outline, stations),
+        }
+    }
+}
+
+impl Speaker {
+    /// Search TuneIn for radio stations by name.
+    ///
+    /// Sonos doesn't expose music service content through the ContentDirectory, so this searches
+    /// TuneIn directly the same way the Sonos app does. Any speaker can play the results through
+    /// [Speaker::play_tunein_station].
+    pub async fn tunein_search(&self, query: &str) -> Result<Vec<RadioStation>, Error> {
+        let resp = HTTP_CLIENT
+            .get(TUNEIN_SEARCH_URL)
+            .query(&[("query", query), ("types", "station")])
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(SonosError::BadResponse(resp.status().as_u16()).into());
+        }
+
+        let opml = Element::parse(resp.bytes().await?.as_ref())?;
+        let body = opml
+            .get_child("body")
+            .ok_or_else(|| SonosError::ParseError("missing body in TuneIn search results".to_string()))?;
+
+        let mut stations = Vec::new();
+        collect_stations(body, &mut stations);
+
+        Ok(stations)
+    }
+}
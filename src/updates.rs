@@ -0,0 +1,59 @@
+use crate::device::{get_child_element_text, Speaker};
+use crate::service::Service;
+
+use failure::Error;
+use xmltree::{Element, XMLNode};
+
+/// A software update available to the household.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingUpdate {
+    /// Firmware version the update installs (eg. 57.3-77280)
+    pub version: String,
+    /// URL the update is downloaded from
+    pub update_url: String,
+    /// URL of the manifest describing what's in the update
+    pub manifest_url: Option<String>,
+    /// Size of the download in bytes, if the speaker reported it
+    pub download_size: Option<u64>,
+}
+
+/// Parse an UpdateItem element, which describes an update or is empty if there isn't one.
+fn parse_update_item(el: &Element) -> Option<PendingUpdate> {
+    let attribute = |name: &str| el.attributes.get(name).filter(|value| !value.is_empty()).cloned();
+
+    Some(PendingUpdate {
+        version: attribute("Version")?,
+        update_url: attribute("UpdateURL")?,
+        manifest_url: attribute("ManifestURL"),
+        download_size: attribute("DownloadSize").and_then(|size| size.parse().ok()),
+    })
+}
+
+impl Speaker {
+    /// Get the software updates available to the household, which is empty if every speaker is
+    /// up to date.
+    ///
+    /// Sonos reports updates through the ZoneGroupTopology service's CheckForUpdate action.
+    pub async fn get_pending_updates(&self) -> Result<Vec<PendingUpdate>, Error> {
+        let resp = self.soap(
+            &Service::ZONE_GROUP_TOPOLOGY,
+            "CheckForUpdate",
+            "<UpdateType>All</UpdateType><CachedOnly>0</CachedOnly><Version></Version>",
+            false,
+        ).await?;
+
+        let items = Element::parse(get_child_element_text(&resp, "UpdateItem")?.as_bytes())?;
+
+        // a single update is returned as the root element, rather than as a list of them
+        if items.name == "UpdateItem" {
+            return Ok(parse_update_item(&items).into_iter().collect());
+        }
+
+        Ok(items
+            .children
+            .iter()
+            .filter_map(XMLNode::as_element)
+            .filter_map(parse_update_item)
+            .collect())
+    }
+}
@@ -0,0 +1,103 @@
+use crate::device::Speaker;
+use crate::event_server::EventServer;
+use crate::events::TopologyEventReceiver;
+use crate::topology::{ZoneGroup, ZoneMember};
+
+use failure::Error;
+use futures::future::{self, Either};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::watch;
+
+/// The group a speaker is in, kept up to date as speakers join and leave it.
+///
+/// Unlike a [ZoneGroup], which is a snapshot of the topology, a zone follows the group of the
+/// speaker it was created from (see [Speaker::zone]) for as long as it's held.
+#[derive(Clone)]
+pub struct Zone {
+    state: watch::Receiver<ZoneState>,
+}
+
+#[derive(Clone)]
+struct ZoneState {
+    group: ZoneGroup,
+    coordinator: Speaker,
+}
+
+impl Zone {
+    /// The speaker currently coordinating the group.
+    pub fn coordinator(&self) -> Speaker {
+        self.state.borrow().coordinator.clone()
+    }
+
+    /// The speakers currently in the group, including the coordinator.
+    pub fn members(&self) -> Vec<ZoneMember> {
+        self.state.borrow().group.members.clone()
+    }
+
+    /// The group as it currently is.
+    pub fn group(&self) -> ZoneGroup {
+        self.state.borrow().group.clone()
+    }
+
+    /// Wait until the group next changes.
+    ///
+    /// Returns an error if the zone has stopped receiving topology events.
+    pub async fn changed(&mut self) -> Result<(), watch::error::RecvError> {
+        self.state.changed().await
+    }
+}
+
+impl Speaker {
+    /// Follow the group this speaker is in using topology events received by `server`.
+    ///
+    /// The subscription to the speaker is kept alive until every clone of the zone has been
+    /// dropped.
+    pub async fn zone(&self, server: &EventServer) -> Result<Zone, Error> {
+        let events = self.subscribe_to_zone_topology_events(server).await?;
+        let group = self.zone_group().await?;
+        let coordinator = self.coordinator_speaker().await?;
+        let (sender, receiver) = watch::channel(ZoneState { group, coordinator });
+
+        tokio::spawn(follow_group(self.uuid.clone(), events, sender));
+
+        Ok(Zone { state: receiver })
+    }
+}
+
+/// Keep the group of the speaker with the given UUID up to date from `events` until every
+/// receiver of the state has been dropped.
+async fn follow_group(uuid: String, mut events: TopologyEventReceiver, sender: watch::Sender<ZoneState>) {
+    loop {
+        let event = match future::select(Box::pin(sender.closed()), Box::pin(events.recv())).await {
+            Either::Left(_) => return,
+            Either::Right((Ok(event), _)) => event,
+            Either::Right((Err(RecvError::Lagged(_)), _)) => continue,
+            Either::Right((Err(RecvError::Closed), _)) => return,
+        };
+
+        let group = match event.groups.into_iter().find(|group| group.contains(&uuid)) {
+            Some(group) => group,
+            None => continue,
+        };
+
+        let current = sender.borrow().coordinator.clone();
+        let coordinator = if current.uuid == group.coordinator {
+            current
+        } else {
+            let ip = match group.coordinator_member() {
+                Some(member) => member.ip,
+                None => continue,
+            };
+
+            match Speaker::from_ip(ip).await {
+                Ok(speaker) => speaker,
+                Err(e) => {
+                    debug!("Failed to get new coordinator of {}: {}", group.id, e);
+                    continue;
+                }
+            }
+        };
+
+        sender.send_replace(ZoneState { group, coordinator });
+    }
+}
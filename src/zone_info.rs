@@ -0,0 +1,40 @@
+use crate::device::{get_child_element_text, get_child_text, Speaker};
+use crate::service::Service;
+
+use std::net::IpAddr;
+
+use failure::Error;
+
+/// Details about a speaker's hardware and network, as reported by its DeviceProperties service.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneInfo {
+    pub serial_number: String,
+    /// Firmware version of the speaker (eg. 56.0-76060)
+    pub software_version: String,
+    /// Firmware version as it's shown in the Sonos app (eg. 11.2.0)
+    pub display_version: String,
+    pub hardware_version: String,
+    pub ip: IpAddr,
+    pub mac_address: String,
+}
+
+impl Speaker {
+    /// Get details about this speaker's hardware and network in a single call.
+    pub async fn get_zone_info(&self) -> Result<ZoneInfo, Error> {
+        let resp = self.soap(
+            &Service::DEVICE_PROPERTIES,
+            "GetZoneInfo",
+            "",
+            false,
+        ).await?;
+
+        Ok(ZoneInfo {
+            serial_number: get_child_element_text(&resp, "SerialNumber")?.into_owned(),
+            software_version: get_child_element_text(&resp, "SoftwareVersion")?.into_owned(),
+            display_version: get_child_text(&resp, "DisplaySoftwareVersion").unwrap_or_default(),
+            hardware_version: get_child_element_text(&resp, "HardwareVersion")?.into_owned(),
+            ip: get_child_element_text(&resp, "IPAddress")?.parse()?,
+            mac_address: get_child_element_text(&resp, "MACAddress")?.into_owned(),
+        })
+    }
+}
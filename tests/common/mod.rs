@@ -0,0 +1,17 @@
+use sonos::Speaker;
+
+/// Build a speaker that's never talked to, for tests that only look at its description.
+pub fn speaker(name: &str, model: &str, model_number: &str) -> Speaker {
+    Speaker {
+        ip: "192.168.1.10".parse().unwrap(),
+        https: false,
+        port: 1400,
+        model: model.to_string(),
+        model_number: model_number.to_string(),
+        software_version: "56.0-76060".to_string(),
+        hardware_version: "1.20.1.6-2".to_string(),
+        serial_number: "00-0E-58-00-00-00:0".to_string(),
+        name: name.to_string(),
+        uuid: format!("RINCON_{}", name),
+    }
+}
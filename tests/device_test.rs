@@ -0,0 +1,27 @@
+extern crate sonos;
+
+mod common;
+
+use sonos::{SonosError, Speaker, TransportState};
+
+use std::convert::TryFrom;
+
+fn speaker() -> Speaker {
+    Speaker {
+        ip: "192.168.1.50".parse().unwrap(),
+        ..common::speaker("Living Room", "Sonos Era 100", "S39")
+    }
+}
+
+#[test]
+fn speaker_display() {
+    assert_eq!(speaker().to_string(), "Living Room (Sonos Era 100, 192.168.1.50)");
+}
+
+#[test]
+fn transport_state_from_str() {
+    assert_eq!(TransportState::try_from("PLAYING").unwrap(), TransportState::Playing);
+    assert_eq!(TransportState::try_from("PAUSED_PLAYBACK").unwrap(), TransportState::PausedPlayback);
+    assert_eq!(TransportState::try_from("STOPPED").unwrap(), TransportState::Stopped);
+    assert!(matches!(TransportState::try_from("playing"), Err(SonosError::ParseError(_))));
+}
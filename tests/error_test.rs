@@ -0,0 +1,32 @@
+extern crate sonos;
+
+use sonos::{AVTransportError, SonosError};
+
+use std::convert::TryFrom;
+
+#[test]
+fn av_transport_error_from_str() {
+    assert_eq!(AVTransportError::try_from("701").unwrap(), AVTransportError::TransitionNotAvailable);
+    assert_eq!(AVTransportError::try_from("999").unwrap(), AVTransportError::Unknown);
+    assert!(matches!(AVTransportError::try_from("oops"), Err(SonosError::ParseError(_))));
+}
+
+#[test]
+fn av_transport_error_code() {
+    assert_eq!(AVTransportError::IllegalSeekTarget.code(), 711);
+    assert_eq!(AVTransportError::from(402).code(), 402);
+    assert_eq!(AVTransportError::Unknown.code(), 0);
+}
+
+#[test]
+fn av_transport_error_display() {
+    assert_eq!(AVTransportError::TransitionNotAvailable.to_string(), "transition not available (701)");
+    assert_eq!(AVTransportError::Unknown.to_string(), "unknown error");
+    assert_eq!(
+        SonosError::from(AVTransportError::IllegalSeekTarget).to_string(),
+        "Received error illegal seek target (711) from Sonos speaker"
+    );
+
+    let error: Box<dyn std::error::Error> = Box::new(AVTransportError::InvalidArgs);
+    assert_eq!(error.to_string(), "invalid arguments (402)");
+}
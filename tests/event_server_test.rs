@@ -0,0 +1,52 @@
+#![cfg(feature = "events")]
+
+extern crate sonos;
+
+use reqwest::Method;
+
+const VOLUME_EVENT: &str = r#"<e:propertyset xmlns:e="urn:schemas-upnp-org:event-1-0">
+    <e:property>
+        <LastChange>&lt;Event xmlns="urn:schemas-upnp-org:metadata-1-0/RCS/"&gt;&lt;InstanceID val="0"&gt;&lt;Volume channel="Master" val="12"/&gt;&lt;Mute channel="Master" val="0"/&gt;&lt;/InstanceID&gt;&lt;/Event&gt;</LastChange>
+    </e:property>
+</e:propertyset>"#;
+
+async fn notify(url: &str, body: &str) -> u16 {
+    reqwest::Client::new()
+        .request(Method::from_bytes(b"NOTIFY").unwrap(), url)
+        .header("SID", "uuid:RINCON_000E58000000001400_sub0000000001")
+        .header("SEQ", "3")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to send event")
+        .status()
+        .as_u16()
+}
+
+#[tokio::test]
+async fn dispatches_events_to_route() {
+    let server = sonos::EventServer::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let route = server.add_raw_route();
+    let mut events = route.subscribe();
+
+    let url = format!("http://{}{}", server.local_addr(), route.path());
+    assert_eq!(notify(&url, VOLUME_EVENT).await, 200);
+
+    let event = events.recv().await.expect("No event dispatched");
+    assert_eq!(event.seq, 3);
+    assert_eq!(event.last_change.len(), 2);
+    assert_eq!(event.last_change[0].instance, "0");
+    assert_eq!(event.last_change[0].name, "Volume");
+    assert_eq!(event.last_change[0].channel.as_deref(), Some("Master"));
+    assert_eq!(event.last_change[0].value, "12");
+}
+
+#[tokio::test]
+async fn removes_dropped_routes() {
+    let server = sonos::EventServer::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let route = server.add_raw_route();
+    let url = format!("http://{}{}", server.local_addr(), route.path());
+
+    drop(route);
+    assert_eq!(notify(&url, VOLUME_EVENT).await, 404);
+}
@@ -0,0 +1,31 @@
+extern crate sonos;
+
+mod common;
+
+use sonos::{Household, Speaker};
+
+#[test]
+fn find_speakers_by_model() {
+    let household = Household {
+        speakers: vec![
+            common::speaker("Kitchen", "Sonos Era 100", "S39"),
+            common::speaker("Living Room", "Sonos Arc", "S19"),
+            common::speaker("Bedroom", "Sonos Era 100", "S39"),
+        ],
+        groups: Vec::new(),
+    };
+
+    let names = |speakers: Vec<&Speaker>| {
+        speakers.into_iter().map(|s| s.name.clone()).collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names(household.find_speakers_by_model("Sonos Era 100")),
+        ["Kitchen", "Bedroom"]
+    );
+    assert_eq!(
+        names(household.find_speakers_by_model_number("S19")),
+        ["Living Room"]
+    );
+    assert!(household.find_speakers_by_model("Sonos Era 300").is_empty());
+}
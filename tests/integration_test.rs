@@ -24,7 +24,7 @@ async fn volume() {
     device.set_volume(2).await.expect("Failed to get volume");
     assert_eq!(
         device.volume().await.expect("Failed to get volume"),
-        2 as u8,
+        2_u8,
         "Volume was not updated."
     );
 }
@@ -33,21 +33,15 @@ async fn volume() {
 async fn muted() {
     let device = get_speaker().await;
     device.mute().await.expect("Couldn't mute player");
-    assert_eq!(
-        device
-            .muted()
-            .await
-            .expect("Failed to get current mute status"),
-        true
-    );
+    assert!(device
+        .muted()
+        .await
+        .expect("Failed to get current mute status"));
     device.unmute().await.expect("Couldn't unmute player");
-    assert_eq!(
-        device
-            .muted()
-            .await
-            .expect("Failed to get current mute status"),
-        false
-    );
+    assert!(!device
+        .muted()
+        .await
+        .expect("Failed to get current mute status"));
 }
 
 #[tokio::test]
@@ -55,25 +49,25 @@ async fn playback_state() {
     let device = get_speaker().await;
 
     device.play().await.expect("Couldn't play track");
-    assert!(match device.transport_state().await.unwrap() {
-        TransportState::Playing | TransportState::Transitioning => true,
-        _ => false,
-    });
+    assert!(matches!(
+        device.transport_state().await.unwrap(),
+        TransportState::Playing | TransportState::Transitioning
+    ));
 
     device.pause().await.expect("Couldn't pause track");
-    assert!(match device.transport_state().await.unwrap() {
-        TransportState::PausedPlayback | TransportState::Transitioning => true,
-        _ => false,
-    });
+    assert!(matches!(
+        device.transport_state().await.unwrap(),
+        TransportState::PausedPlayback | TransportState::Transitioning
+    ));
 
     device.stop().await.expect("Couldn't stop track");
     let state = device.transport_state().await.unwrap();
     // eprintln!("{:#?}", state);
     // This returns PausedPlayback on my speaker - is stop no longer supported?
-    assert!(match state {
-        TransportState::Stopped | TransportState::Transitioning => true,
-        _ => false,
-    });
+    assert!(matches!(
+        state,
+        TransportState::Stopped | TransportState::Transitioning
+    ));
 }
 
 #[tokio::test]
@@ -116,3 +110,905 @@ async fn fail_on_set_invalid_volume() {
         .await
         .expect_err("Didn't fail on invalid volume");
 }
+
+#[tokio::test]
+async fn soap_any() {
+    let device = get_speaker().await;
+    let resp = device
+        .soap_any(
+            "MediaRenderer/RenderingControl/Control",
+            "urn:schemas-upnp-org:service:RenderingControl:1",
+            "GetVolume",
+            "<InstanceID>0</InstanceID><Channel>Master</Channel>",
+        )
+        .await
+        .expect("Failed to call GetVolume");
+    assert!(resp.get_child("CurrentVolume").is_some());
+}
+
+#[tokio::test]
+async fn http_get() {
+    let device = get_speaker().await;
+    let body = device
+        .http_get("/xml/device_description.xml")
+        .await
+        .expect("Failed to fetch device description");
+    assert!(!body.is_empty());
+}
+
+#[tokio::test]
+async fn zone_group_state() {
+    let device = get_speaker().await;
+    let groups = device
+        .zone_group_state()
+        .await
+        .expect("Failed to get zone group state");
+    assert!(groups.iter().any(|g| g.contains(&device.uuid)));
+}
+
+#[tokio::test]
+async fn is_coordinator() {
+    let device = get_speaker().await;
+    device.ungroup().await.expect("Failed to ungroup speaker");
+    assert!(device
+        .is_coordinator()
+        .await
+        .expect("Failed to check coordinator status"));
+}
+
+#[tokio::test]
+async fn coordinator_speaker() {
+    let device = get_speaker().await;
+    device.ungroup().await.expect("Failed to ungroup speaker");
+    let coordinator = device
+        .coordinator_speaker()
+        .await
+        .expect("Failed to get coordinator");
+    assert_eq!(coordinator.uuid, device.uuid);
+}
+
+#[tokio::test]
+async fn protocol_info() {
+    let device = get_speaker().await;
+    let info = device
+        .get_protocol_info()
+        .await
+        .expect("Failed to get protocol info");
+    assert!(!info.sink.is_empty(), "Speaker reported no playable protocols");
+}
+
+#[cfg(feature = "events")]
+#[tokio::test]
+async fn transport_events() {
+    let device = get_speaker().await;
+    let server = sonos::EventServer::new().expect("Failed to start event server");
+    let mut events = device
+        .subscribe_to_transport_events(&server)
+        .await
+        .expect("Failed to subscribe to transport events");
+
+    // the first event after subscribing contains the current state
+    let event = events.recv().await.expect("Didn't receive initial event");
+    assert!(event.transport_state.is_some());
+}
+
+#[cfg(feature = "events")]
+#[tokio::test]
+async fn volume_events() {
+    let device = get_speaker().await;
+    let server = sonos::EventServer::new().expect("Failed to start event server");
+    let mut events = device
+        .subscribe_to_volume_events(&server)
+        .await
+        .expect("Failed to subscribe to volume events");
+    events.recv().await.expect("Didn't receive initial event");
+
+    let volume = device.volume().await.expect("Failed to get volume");
+    let new_volume = if volume == 2 { 3 } else { 2 };
+    device.set_volume(new_volume).await.expect("Failed to set volume");
+
+    let event = events.recv().await.expect("Didn't receive volume change");
+    assert_eq!(event.volume.get("Master"), Some(&new_volume));
+    assert_eq!(event.mute, None, "Unchanged mute state was re-emitted");
+}
+
+#[cfg(feature = "events")]
+#[tokio::test]
+async fn queue_events() {
+    let device = get_speaker().await;
+    let server = sonos::EventServer::new().expect("Failed to start event server");
+    let mut events = device
+        .subscribe_to_queue_events(&server)
+        .await
+        .expect("Failed to subscribe to queue events");
+
+    let event = events.recv().await.expect("Didn't receive initial event");
+    assert_eq!(event.queue_id, 0);
+}
+
+#[cfg(feature = "events")]
+#[tokio::test]
+async fn zone_topology_events() {
+    let device = get_speaker().await;
+    let server = sonos::EventServer::new().expect("Failed to start event server");
+    let mut events = device
+        .subscribe_to_zone_topology_events(&server)
+        .await
+        .expect("Failed to subscribe to topology events");
+
+    let event = events.recv().await.expect("Didn't receive initial event");
+    assert!(event.groups.iter().any(|g| g.contains(&device.uuid)));
+}
+
+#[tokio::test]
+async fn alarms() {
+    let device = get_speaker().await;
+    device.alarms().await.expect("Failed to list alarms");
+}
+
+#[tokio::test]
+async fn wait_for_state() {
+    let device = get_speaker().await;
+    device.play().await.expect("Failed to play");
+    device
+        .wait_for_state(TransportState::Playing, std::time::Duration::from_secs(10))
+        .await
+        .expect("Speaker didn't start playing");
+    device.pause().await.expect("Failed to pause");
+}
+
+#[tokio::test]
+async fn play_and_pause_and_wait() {
+    let device = get_speaker().await;
+    let timeout = std::time::Duration::from_secs(10);
+    device
+        .play_and_wait_for_playing(timeout)
+        .await
+        .expect("Speaker didn't start playing");
+    device
+        .pause_and_wait(timeout)
+        .await
+        .expect("Speaker didn't pause");
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn clock_time() {
+    let device = get_speaker().await;
+    device.clock_time().await.expect("Failed to get clock time");
+}
+
+#[tokio::test]
+async fn set_clock_format() {
+    let device = get_speaker().await;
+    device
+        .set_clock_format(sonos::ClockFormat::TwentyFourHour)
+        .await
+        .expect("Failed to set clock format");
+}
+
+#[tokio::test]
+async fn household_alarm_enabled() {
+    let device = get_speaker().await;
+    let enabled = device
+        .household_alarm_enabled()
+        .await
+        .expect("Failed to get alarm state");
+    device
+        .set_household_alarm_enabled(enabled)
+        .await
+        .expect("Failed to set alarm state");
+}
+
+#[tokio::test]
+async fn firmware_version() {
+    let device = get_speaker().await;
+    assert!(device.firmware_version().starts_with(&device.firmware_major().to_string()));
+}
+
+#[tokio::test]
+async fn music_library() {
+    use futures::{StreamExt, TryStreamExt};
+
+    let device = get_speaker().await;
+    device
+        .music_library()
+        .browse_artists()
+        .take(5)
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to browse artists");
+}
+
+#[tokio::test]
+async fn content_directory_browse() {
+    let device = get_speaker().await;
+    device
+        .content_directory_browse("SQ:")
+        .await
+        .expect("Failed to browse playlists");
+}
+
+#[tokio::test]
+async fn favorites() {
+    use futures::TryStreamExt;
+
+    let device = get_speaker().await;
+    let favorites = device.favorites().await.expect("Failed to list favorites");
+    let streamed = device
+        .favorites_stream(2)
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to stream favorites");
+    assert_eq!(favorites, streamed);
+}
+
+#[tokio::test]
+async fn add_to_favorites() {
+    let device = get_speaker().await;
+    let id = device
+        .add_to_favorites("x-rincon-mp3radio://example.com/stream.mp3", "sonos.rs test", "")
+        .await
+        .expect("Failed to add favorite");
+
+    let favorites = device.favorites().await.expect("Failed to list favorites");
+    assert!(favorites.iter().any(|favorite| favorite.id == id));
+
+    device
+        .remove_from_favorites(&id)
+        .await
+        .expect("Failed to remove favorite");
+
+    let favorites = device.favorites().await.expect("Failed to list favorites");
+    assert!(!favorites.iter().any(|favorite| favorite.id == id));
+}
+
+#[tokio::test]
+async fn remove_from_favorites_rejects_other_ids() {
+    let device = get_speaker().await;
+    let error = device
+        .remove_from_favorites("SQ:1")
+        .await
+        .expect_err("Removed a playlist as a favorite");
+
+    assert!(matches!(
+        error.downcast_ref::<sonos::SonosError>(),
+        Some(sonos::SonosError::InvalidObjectId(_))
+    ));
+}
+
+#[tokio::test]
+async fn alarm_clock_info() {
+    let device = get_speaker().await;
+    let info = device
+        .get_alarm_clock_info()
+        .await
+        .expect("Failed to get alarm clock info");
+    assert_eq!(info.alarms, device.alarms().await.expect("Failed to list alarms"));
+}
+
+#[tokio::test]
+async fn set_av_transport_speed() {
+    let device = get_speaker().await;
+    device
+        .set_av_transport_speed(1.0)
+        .await
+        .expect("Failed to play at normal speed");
+    device.pause().await.expect("Failed to pause");
+}
+
+#[tokio::test]
+async fn queue_item_at() {
+    let device = get_speaker().await;
+    let queue = device.queue();
+    let items = queue.list().await.expect("Failed to list queue");
+
+    if let Some(first) = items.first() {
+        let item = queue.item_at(first.position).await.expect("Failed to get queue item");
+        assert_eq!(item.uri, first.uri);
+    }
+
+    assert!(queue.item_at(items.len() as u64 + 1).await.is_err());
+}
+
+#[tokio::test]
+async fn remove_current_track_outside_queue() {
+    let device = get_speaker().await;
+    device.play_line_in().await.expect("Failed to play line-in");
+
+    let error = device
+        .remove_current_track_from_queue()
+        .await
+        .expect_err("Removed a track while playing line-in");
+
+    assert!(matches!(
+        error.downcast_ref::<sonos::SonosError>(),
+        Some(sonos::SonosError::NotPlayingFromQueue)
+    ));
+}
+
+#[tokio::test]
+async fn speaker_type() {
+    let device = get_speaker().await;
+    assert_eq!(
+        device.get_speaker_type().await.expect("Failed to get speaker type"),
+        sonos::SpeakerType::ZonePlayer
+    );
+}
+
+#[tokio::test]
+async fn all_zone_members() {
+    let device = get_speaker().await;
+    let members = device
+        .get_all_zone_members()
+        .await
+        .expect("Failed to get zone members");
+
+    assert!(members.iter().any(|member| member.uuid == device.uuid));
+    assert_eq!(members.iter().filter(|member| member.is_coordinator).count(), 1);
+}
+
+#[cfg(feature = "watch")]
+#[tokio::test]
+async fn state_watch() {
+    let device = get_speaker().await;
+    let mut state = device
+        .state_watch_with_interval(std::time::Duration::from_millis(500))
+        .await
+        .expect("Failed to watch state");
+
+    device.set_volume(10).await.expect("Failed to set volume");
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        while state.borrow().volume != 10 {
+            state.changed().await.expect("State watch stopped");
+        }
+    })
+    .await
+    .expect("Volume change wasn't picked up");
+}
+
+#[tokio::test]
+async fn source() {
+    let device = get_speaker().await;
+    device.play_line_in().await.expect("Failed to play line-in");
+    assert_eq!(device.source().await.expect("Failed to get source"), sonos::AudioSource::LineIn);
+    assert!(device.is_playing_line_in().await.expect("Failed to get source"));
+    assert!(!device.is_playing_tv().await.expect("Failed to get source"));
+}
+
+#[tokio::test]
+async fn play_uri_next_in_queue() {
+    let device = get_speaker().await;
+    let uri = "x-rincon-mp3radio://example.com/stream.mp3";
+    device
+        .play_uri_next_in_queue(uri)
+        .await
+        .expect("Failed to play next in queue");
+    device.pause().await.expect("Failed to pause");
+
+    assert_eq!(device.track().await.expect("Failed to get track").uri, uri);
+    device
+        .remove_current_track_from_queue()
+        .await
+        .expect("Failed to remove track");
+}
+
+#[tokio::test]
+async fn position_info() {
+    let device = get_speaker().await;
+    let raw = device
+        .get_position_info_raw()
+        .await
+        .expect("Failed to get position info");
+    assert!(raw.get_child("AbsTime").is_some());
+    device.position().await.expect("Failed to get position");
+}
+
+#[tokio::test]
+async fn soap_request() {
+    let device = get_speaker().await;
+    let resp = sonos::SoapRequest::for_service(&sonos::Service::RENDERING_CONTROL)
+        .action("GetVolume")
+        .payload("<InstanceID>0</InstanceID><Channel>Master</Channel>")
+        .send(&device)
+        .await
+        .expect("Failed to call GetVolume");
+    assert!(resp.get_child("CurrentVolume").is_some());
+}
+
+#[tokio::test]
+async fn announce_to_group() {
+    let device = get_speaker().await;
+    let volume = device.volume().await.expect("Failed to get volume");
+    device
+        .announce_to_group("x-rincon-mp3radio://example.com/chime.mp3", 20)
+        .await
+        .expect("Failed to announce");
+    assert_eq!(device.volume().await.expect("Failed to get volume"), volume);
+}
+
+#[tokio::test]
+async fn household_pause_and_play_all() {
+    let device = get_speaker().await;
+    let household = sonos::Household::from_speaker(&device)
+        .await
+        .expect("Failed to get household");
+
+    assert!(household.speakers.iter().any(|speaker| speaker.uuid == device.uuid));
+    assert_eq!(household.coordinators().len(), household.groups.len());
+
+    household.play_all().await.expect("Failed to play all");
+    household.pause_all().await.expect("Failed to pause all");
+}
+
+#[tokio::test]
+async fn group_volume() {
+    let device = get_speaker().await;
+    device.set_group_volume(15).await.expect("Failed to set group volume");
+    assert_eq!(device.group_volume().await.expect("Failed to get group volume"), 15);
+}
+
+#[tokio::test]
+async fn household_set_all_volumes() {
+    let device = get_speaker().await;
+    let household = sonos::Household::from_speaker(&device)
+        .await
+        .expect("Failed to get household");
+    household.set_all_volumes(15).await.expect("Failed to set volumes");
+}
+
+#[tokio::test]
+async fn art_for_current_track() {
+    let device = get_speaker().await;
+    let track = device.track().await.expect("Failed to get track info");
+    let art = device
+        .art_for_current_track()
+        .await
+        .expect("Failed to get album art");
+
+    if track.album_art_uri.is_none() {
+        assert!(art.is_none());
+    }
+}
+
+#[tokio::test]
+async fn radio_favorites() {
+    let device = get_speaker().await;
+    for favorite in device.radio_favorites().await.expect("Failed to get radio favorites") {
+        assert!(favorite.id.starts_with("R:0/0/"));
+    }
+}
+
+#[tokio::test]
+async fn recently_played() {
+    let device = get_speaker().await;
+    device.recently_played().await.expect("Failed to get recently played");
+}
+
+#[tokio::test]
+async fn play_tunein_station() {
+    let device = get_speaker().await;
+    device
+        .play_tunein_station("s24940")
+        .await
+        .expect("Failed to play TuneIn station");
+    assert_eq!(device.source().await.expect("Failed to get source"), sonos::AudioSource::Radio);
+    device.stop().await.expect("Failed to stop");
+}
+
+#[tokio::test]
+async fn sound_settings() {
+    let device = get_speaker().await;
+    let settings = device.sound_settings().await.expect("Failed to get sound settings");
+
+    device
+        .apply_sound_settings(&sonos::SoundSettings { bass: 2, ..settings })
+        .await
+        .expect("Failed to apply sound settings");
+    assert_eq!(device.bass().await.expect("Failed to get bass"), 2);
+
+    device
+        .apply_sound_settings(&settings)
+        .await
+        .expect("Failed to restore sound settings");
+}
+
+#[tokio::test]
+async fn current_group_id() {
+    let device = get_speaker().await;
+    let group_id = device.current_group_id().await.expect("Failed to get group id");
+    let groups = device
+        .zone_group_state()
+        .await
+        .expect("Failed to get zone group state");
+    assert!(groups.iter().any(|g| g.id == group_id && g.contains(&device.uuid)));
+}
+
+#[tokio::test]
+async fn is_in_same_group_as() {
+    let device = get_speaker().await;
+    assert!(device
+        .is_in_same_group_as(&device)
+        .await
+        .expect("Failed to check group"));
+}
+
+#[tokio::test]
+async fn play_from_queue_at() {
+    let device = get_speaker().await;
+    device.play_from_queue_at(1).await.expect("Failed to play from queue");
+    assert_eq!(device.track().await.expect("Failed to get track info").queue_position, 1);
+    device.pause().await.expect("Failed to pause");
+}
+
+#[tokio::test]
+async fn subscribe_renew_unsubscribe() {
+    let device = get_speaker().await;
+    let sid = device
+        .subscribe(
+            sonos::Service::AV_TRANSPORT.event_path(),
+            "http://127.0.0.1:3400/",
+            60,
+        )
+        .await
+        .expect("Failed to subscribe");
+
+    device
+        .renew_subscription(&sid, 60)
+        .await
+        .expect("Failed to renew subscription");
+    device.unsubscribe(&sid).await.expect("Failed to unsubscribe");
+}
+
+#[tokio::test]
+async fn av_transport_state_variables() {
+    let device = get_speaker().await;
+    let variables = device
+        .get_av_transport_state_variables()
+        .await
+        .expect("Failed to query state variables");
+    assert!(variables.contains_key("TransportState"));
+}
+
+#[tokio::test]
+async fn media_renderer_description() {
+    let device = get_speaker().await;
+    let description = device
+        .get_media_renderer_description()
+        .await
+        .expect("Failed to get MediaRenderer description");
+    assert!(description.get_child("serviceList").is_some());
+}
+
+#[tokio::test]
+async fn action_list() {
+    let device = get_speaker().await;
+    let actions = device
+        .get_action_list("/xml/AVTransport1.xml")
+        .await
+        .expect("Failed to get action list");
+    assert!(actions.iter().any(|action| action == "Play"));
+}
+
+#[tokio::test]
+async fn state_variable_list() {
+    let device = get_speaker().await;
+    let variables = device
+        .get_state_variable_list("/xml/AVTransport1.xml")
+        .await
+        .expect("Failed to get state variable list");
+    let transport_state = variables
+        .iter()
+        .find(|variable| variable.name == "TransportState")
+        .expect("Missing TransportState");
+    assert!(transport_state.allowed_values.iter().any(|value| value == "PLAYING"));
+}
+
+#[tokio::test]
+async fn service_shortcuts() {
+    let device = get_speaker().await;
+    device
+        .avt_action("GetTransportInfo", "<InstanceID>0</InstanceID>")
+        .await
+        .expect("Failed to call AVTransport");
+    device
+        .rc_action("GetVolume", "<InstanceID>0</InstanceID><Channel>Master</Channel>")
+        .await
+        .expect("Failed to call RenderingControl");
+    device
+        .cd_action("GetSystemUpdateID", "")
+        .await
+        .expect("Failed to call ContentDirectory");
+}
+
+#[tokio::test]
+async fn queue_total() {
+    let device = get_speaker().await;
+    let total = device.get_queue_total().await.expect("Failed to get queue total");
+    assert_eq!(
+        total as usize,
+        device.queue().list().await.expect("Failed to list queue").len()
+    );
+}
+
+#[tokio::test]
+async fn zone_info() {
+    let device = get_speaker().await;
+    let info = device.get_zone_info().await.expect("Failed to get zone info");
+    assert_eq!(info.ip, device.ip);
+    assert_eq!(info.software_version, device.software_version);
+}
+
+#[tokio::test]
+async fn container_id_for_service() {
+    let device = get_speaker().await;
+    assert_eq!(
+        device
+            .container_id_for_service("TuneIn")
+            .await
+            .expect("Failed to get container id"),
+        "SA_RINCON65031_"
+    );
+}
+
+#[tokio::test]
+async fn music_service_list() {
+    let device = get_speaker().await;
+    let services = device
+        .get_music_service_list()
+        .await
+        .expect("Failed to get music services");
+    let tunein = services
+        .iter()
+        .find(|service| service.name == "TuneIn")
+        .expect("Missing TuneIn");
+    assert_eq!(tunein.id, "254");
+}
+
+#[tokio::test]
+async fn speaker_builder() {
+    let speakers = sonos::SpeakerBuilder::new()
+        .timeout(std::time::Duration::from_secs(5))
+        .discover_and_build()
+        .await
+        .expect("Failed to discover speakers");
+    assert!(!speakers.is_empty());
+}
+
+#[tokio::test]
+async fn raw_device_description() {
+    let device = get_speaker().await;
+    let description = device
+        .raw_device_description()
+        .await
+        .expect("Failed to get device description");
+    assert!(description.contains(&device.uuid));
+}
+
+#[tokio::test]
+async fn raw_topology() {
+    let device = get_speaker().await;
+    let topology = device.raw_topology().await.expect("Failed to get topology");
+    assert!(topology.contains(&device.uuid));
+}
+
+#[tokio::test]
+async fn pending_updates() {
+    let device = get_speaker().await;
+    for update in device.get_pending_updates().await.expect("Failed to check for updates") {
+        assert!(!update.version.is_empty());
+    }
+}
+
+#[cfg(feature = "events")]
+#[tokio::test]
+async fn zone() {
+    let device = get_speaker().await;
+    let server = sonos::EventServer::new().expect("Failed to start event server");
+    let zone = device.zone(&server).await.expect("Failed to follow zone");
+
+    assert!(zone.group().contains(&device.uuid));
+    assert!(zone.members().iter().any(|member| member.uuid == zone.coordinator().uuid));
+}
+
+#[tokio::test]
+async fn save_queue() {
+    let device = get_speaker().await;
+    let playlist = device.save_queue("sonos.rs test").await.expect("Failed to save queue");
+    assert!(playlist.id.starts_with("SQ:"));
+    assert_eq!(playlist.title, "sonos.rs test");
+    device.pause().await.expect("Failed to pause");
+}
+
+#[tokio::test]
+async fn load_playlist_into_queue() {
+    let device = get_speaker().await;
+    let playlist = device
+        .queue()
+        .save_as_playlist("sonos.rs test")
+        .await
+        .expect("Failed to save queue");
+    let total = device.get_queue_total().await.expect("Failed to get queue total");
+
+    let added = device
+        .load_playlist_into_queue(&playlist.id, 1)
+        .await
+        .expect("Failed to load playlist");
+    assert_eq!(
+        device.get_queue_total().await.expect("Failed to get queue total"),
+        total + u64::from(added)
+    );
+}
+
+#[tokio::test]
+async fn get_input_av_source() {
+    let device = get_speaker().await;
+    device.play_line_in().await.expect("Failed to play line in");
+
+    let source = device.get_input_av_source().await.expect("Failed to get input source");
+    assert_eq!(source.source_type, sonos::AVSourceType::LineIn);
+    assert!(!source.description.is_empty());
+    device.stop().await.expect("Failed to stop");
+}
+
+#[tokio::test]
+async fn set_input_av_source() {
+    let device = get_speaker().await;
+
+    if device.supports_line_in() {
+        device
+            .set_input_av_source(sonos::AVSourceType::LineIn)
+            .await
+            .expect("Failed to set input source");
+        assert!(device.is_playing_line_in().await.expect("Failed to get source"));
+        device.stop().await.expect("Failed to stop");
+    } else {
+        assert!(device.set_input_av_source(sonos::AVSourceType::LineIn).await.is_err());
+    }
+}
+
+#[tokio::test]
+async fn get_bass_capabilities() {
+    let device = get_speaker().await;
+    let bass = device.get_bass_capabilities().await.expect("Failed to get bass capabilities");
+    assert!(bass.supported);
+    assert!(bass.min < 0 && bass.max > 0);
+}
+
+#[tokio::test]
+async fn reset_eq() {
+    let device = get_speaker().await;
+    device.set_bass(4).await.expect("Failed to set bass");
+    device.reset_eq().await.expect("Failed to reset EQ");
+
+    let settings = device.sound_settings().await.expect("Failed to get sound settings");
+    assert_eq!((settings.bass, settings.treble, settings.loudness), (0, 0, false));
+}
+
+#[tokio::test]
+async fn audio_delay() {
+    let device = get_speaker().await;
+
+    if device.supports_tv() {
+        device
+            .set_audio_delay(std::time::Duration::from_millis(100))
+            .await
+            .expect("Failed to set audio delay");
+        assert_eq!(
+            device.get_audio_delay().await.expect("Failed to get audio delay"),
+            std::time::Duration::from_millis(100)
+        );
+    }
+}
+
+#[tokio::test]
+async fn deduplicate_queue() {
+    let device = get_speaker().await;
+    let queue = device.queue();
+    let uri = queue.list().await.expect("Failed to list queue")[0].uri.clone();
+
+    queue.add_end(&uri).await.expect("Failed to add track");
+    queue.add_end(&uri).await.expect("Failed to add track");
+    assert!(queue.deduplicate().await.expect("Failed to deduplicate queue") >= 2);
+
+    let uris: Vec<_> = queue.list_all().await.expect("Failed to list queue").into_iter().map(|item| item.uri).collect();
+    assert_eq!(uris.iter().filter(|u| **u == uri).count(), 1);
+}
+
+#[tokio::test]
+async fn av_transport_subscribe() {
+    let device = get_speaker().await;
+    let sid = device
+        .av_transport_subscribe("http://127.0.0.1:3400/notify", 60)
+        .await
+        .expect("Failed to subscribe");
+    assert!(sid.starts_with("uuid:"));
+
+    device
+        .unsubscribe(&sonos::SubscriptionId::new(sonos::Service::AV_TRANSPORT.event_path(), &sid))
+        .await
+        .expect("Failed to unsubscribe");
+}
+
+#[tokio::test]
+async fn zone_member_to_speaker() {
+    let device = get_speaker().await;
+    let members = device.get_all_zone_members().await.expect("Failed to get zone members");
+    let member = members.iter().find(|m| m.uuid == device.uuid).expect("Speaker isn't in its own group");
+
+    assert_eq!(member.to_speaker().await.expect("Failed to fetch speaker").uuid, device.uuid);
+}
+
+#[tokio::test]
+async fn play_audio_clip() {
+    let device = get_speaker().await;
+    let result = device
+        .play_audio_clip("https://www.soundjay.com/misc/sounds/bell-ringing-05.mp3", Some(20))
+        .await;
+
+    assert_eq!(result.is_ok(), device.supports_feature(sonos::SonosFeature::AudioClip));
+}
+
+#[tokio::test]
+async fn cancel_audio_clip() {
+    let device = get_speaker().await;
+
+    if device.supports_feature(sonos::SonosFeature::AudioClip) {
+        let id = device
+            .play_audio_clip("https://www.soundjay.com/misc/sounds/bell-ringing-05.mp3", Some(20))
+            .await
+            .expect("Failed to play audio clip");
+        device.cancel_audio_clip(&id).await.expect("Failed to cancel audio clip");
+    }
+}
+
+#[tokio::test]
+async fn play_container() {
+    let device = get_speaker().await;
+    let album = device
+        .content_directory_browse("A:ALBUM")
+        .await
+        .expect("Failed to browse albums")
+        .into_iter()
+        .find_map(|item| match item {
+            sonos::LibraryItem::Album { id, .. } => Some(id),
+            _ => None,
+        })
+        .expect("Music library has no albums");
+
+    device
+        .av_transport_play_container(&album, sonos::QueuePolicy::Replace)
+        .await
+        .expect("Failed to play container");
+    assert!(device.get_queue_total().await.expect("Failed to get queue total") > 0);
+    device.pause().await.expect("Failed to pause");
+}
+
+#[tokio::test]
+async fn tunein_search() {
+    let device = get_speaker().await;
+    let stations = device.tunein_search("BBC Radio 1").await.expect("Failed to search TuneIn");
+
+    let station = stations.first().expect("No stations found");
+    assert!(station.id.starts_with('s'));
+    assert!(station.uri.starts_with("x-sonosapi-stream:"));
+}
+
+#[tokio::test]
+async fn from_uuid() {
+    let device = get_speaker().await;
+    let speaker = sonos::Speaker::from_uuid(&device.uuid).await.expect("Failed to find speaker");
+
+    assert_eq!(speaker.ip, device.ip);
+    assert_eq!(speaker.name, device.name);
+}
+
+#[tokio::test]
+async fn group_members() {
+    let device = get_speaker().await;
+    let members = device.group_members().await.expect("Failed to get group members");
+    let coordinator = device.coordinator_speaker().await.expect("Failed to get coordinator");
+
+    assert!(members.iter().any(|member| member.uuid == device.uuid));
+    assert!(members.iter().any(|member| member.uuid == coordinator.uuid));
+}
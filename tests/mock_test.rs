@@ -0,0 +1,610 @@
+extern crate sonos;
+
+use sonos::{
+    AVSourceType, QueuePolicy, Service, SoapRequest, SonosError, SonosFeature, Speaker, SpeakerBuilder,
+    TransportState,
+};
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const UUID: &str = "RINCON_000E58000000001400";
+
+/// A request received by a [MockSpeaker].
+#[derive(Debug, Clone)]
+struct Request {
+    path: String,
+    /// The action named in the SOAPAction header (eg. GetVolume), if there was one
+    action: Option<String>,
+    body: String,
+}
+
+/// Serves canned responses to the requests a real speaker would handle, recording every request
+/// it receives.
+struct MockSpeaker {
+    port: u16,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockSpeaker {
+    fn start() -> MockSpeaker {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).expect("Failed to bind mock speaker");
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                handle(stream.expect("Failed to accept connection"), &recorded);
+            }
+        });
+
+        MockSpeaker { port, requests }
+    }
+
+    async fn speaker(&self) -> Speaker {
+        SpeakerBuilder::new()
+            .port(self.port)
+            .timeout(Duration::from_secs(5))
+            .build(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .await
+            .expect("Failed to build speaker from mock")
+    }
+
+    /// Check if a request was made for the given path.
+    fn was_requested(&self, path: &str) -> bool {
+        self.requests.lock().unwrap().iter().any(|request| request.path == path)
+    }
+
+    /// Get the body of the last SOAP call made with the given action.
+    fn last_call(&self, action: &str) -> String {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|request| request.action.as_deref() == Some(action))
+            .map(|request| request.body.clone())
+            .unwrap_or_else(|| panic!("{} wasn't called", action))
+    }
+}
+
+/// Read a request from `stream` and record it, then reply to it and close the connection.
+fn handle(mut stream: TcpStream, requests: &Mutex<Vec<Request>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    let mut action = None;
+
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            break;
+        }
+
+        let (name, value) = line.split_once(':').unwrap_or((line, ""));
+        match name.to_ascii_lowercase().as_str() {
+            "content-length" => content_length = value.trim().parse().unwrap(),
+            "soapaction" => action = value.trim().trim_matches('"').split('#').nth(1).map(str::to_string),
+            _ => {}
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    let body = String::from_utf8(body).unwrap();
+
+    // a speaker that's hung accepts the connection but never answers
+    if action.as_deref() == Some("Stall") {
+        thread::sleep(Duration::from_secs(2));
+        return;
+    }
+
+    let (status, response) = match (path.as_str(), action.as_deref()) {
+        ("/xml/device_description.xml", _) => ("200 OK", DEVICE_DESCRIPTION.to_string()),
+        ("/xml/RenderingControl1.xml", _) => ("200 OK", RENDERING_CONTROL_SCPD.to_string()),
+        (path, _) if path.ends_with("/Event") => ("200 OK", String::new()),
+        // an empty topology makes the speaker act as its own coordinator
+        ("/status/topology", _) => ("200 OK", "<ZPSupportInfo></ZPSupportInfo>".to_string()),
+        (_, Some("GetVolume")) => ("200 OK", envelope("GetVolume", "<CurrentVolume>27</CurrentVolume>")),
+        (_, Some("GetTransportInfo")) => (
+            "200 OK",
+            envelope(
+                "GetTransportInfo",
+                "<CurrentTransportState>PAUSED_PLAYBACK</CurrentTransportState>\
+                 <CurrentTransportStatus>OK</CurrentTransportStatus><CurrentSpeed>1</CurrentSpeed>",
+            ),
+        ),
+        (_, Some("GetPositionInfo")) => ("200 OK", envelope("GetPositionInfo", &position_info())),
+        (_, Some("GetEQ")) => ("200 OK", envelope("GetEQ", "<CurrentValue>40</CurrentValue>")),
+        (_, Some("SetEQ")) => ("200 OK", envelope("SetEQ", "")),
+        (_, Some("AddURIToQueue")) => (
+            "200 OK",
+            envelope(
+                "AddURIToQueue",
+                "<FirstTrackNumberEnqueued>3</FirstTrackNumberEnqueued><NumTracksAdded>10</NumTracksAdded>\
+                 <NewQueueLength>12</NewQueueLength>",
+            ),
+        ),
+        (_, Some(action @ ("RemoveAllTracksFromQueue" | "SetAVTransportURI" | "Seek" | "Play"))) => {
+            ("200 OK", envelope(action, ""))
+        }
+        (_, Some("GetZoneGroupState")) => ("200 OK", envelope("GetZoneGroupState", &zone_group_state(stream.local_addr().unwrap().port()))),
+        (_, Some("GetMediaInfo")) => ("200 OK", envelope("GetMediaInfo", &media_info())),
+        (_, Some("LoadAudioClip")) => ("200 OK", envelope("LoadAudioClip", "<Id>clip-7</Id>")),
+        (_, Some("CancelAudioClip")) => ("200 OK", envelope("CancelAudioClip", "")),
+        (_, Some("SaveQueue")) => ("200 OK", envelope("SaveQueue", "<AssignedObjectID>SQ:12</AssignedObjectID>")),
+        (_, Some("Browse")) if body.contains("<ObjectID>SQ:12</ObjectID>") => ("200 OK", envelope("Browse", &playlist_browse())),
+        (_, Some("Browse")) => ("200 OK", envelope("Browse", &queue_browse())),
+        _ => ("404 Not Found", String::new()),
+    };
+
+    // GENA subscriptions are answered in headers rather than the body
+    let headers = if path.ends_with("/Event") {
+        format!("SID: uuid:{}_sub0000000001\r\nTIMEOUT: Second-300\r\n", UUID)
+    } else {
+        String::new()
+    };
+
+    // record the request before replying so it's visible as soon as the call returns
+    requests.lock().unwrap().push(Request {
+        path,
+        action,
+        body,
+    });
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n{}Content-Type: text/xml\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        headers,
+        response.len(),
+        response
+    ).unwrap();
+}
+
+fn escape(xml: &str) -> String {
+    xml.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn envelope(action: &str, arguments: &str) -> String {
+    format!(
+        r#"<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/"><s:Body><u:{action}Response xmlns:u="urn:schemas-upnp-org:service:AVTransport:1">{arguments}</u:{action}Response></s:Body></s:Envelope>"#,
+        action = action,
+        arguments = arguments
+    )
+}
+
+fn position_info() -> String {
+    let metadata = r#"<DIDL-Lite xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/" xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/"><item id="-1" parentID="-1"><res>x-file-cifs://nas/music/song.flac</res><upnp:albumArtURI>/getaa?u=x-file-cifs%3a%2f%2fnas%2fmusic%2fsong.flac</upnp:albumArtURI><dc:title>Song</dc:title><dc:creator>Artist</dc:creator><upnp:album>Album</upnp:album></item></DIDL-Lite>"#;
+
+    format!(
+        "<Track>3</Track><TrackDuration>0:03:30</TrackDuration><TrackMetaData>{}</TrackMetaData>\
+         <TrackURI>x-file-cifs://nas/music/song.flac</TrackURI><RelTime>0:01:05</RelTime>",
+        escape(metadata)
+    )
+}
+
+fn media_info() -> String {
+    let metadata = r#"<DIDL-Lite xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/" xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/"><item id="-1" parentID="-1"><dc:title>Kitchen</dc:title><upnp:class>object.item.audioItem</upnp:class></item></DIDL-Lite>"#;
+
+    format!(
+        "<NrTracks>1</NrTracks><CurrentURI>x-rincon-stream:RINCON_000E58000000001401</CurrentURI>\
+         <CurrentURIMetaData>{}</CurrentURIMetaData>",
+        escape(metadata)
+    )
+}
+
+fn zone_group_state(port: u16) -> String {
+    let state = format!(
+        r#"<ZoneGroupState><ZoneGroups><ZoneGroup Coordinator="{uuid}" ID="{uuid}:57"><ZoneGroupMember UUID="{uuid}" Location="http://127.0.0.1:{port}/xml/device_description.xml" ZoneName="Living Room"/><ZoneGroupMember UUID="RINCON_000E58000000001401" Location="http://127.0.0.1:{port}/xml/device_description.xml" ZoneName="Kitchen"/></ZoneGroup></ZoneGroups></ZoneGroupState>"#,
+        uuid = UUID,
+        port = port
+    );
+
+    format!("<ZoneGroupState>{}</ZoneGroupState>", escape(&state))
+}
+
+fn playlist_browse() -> String {
+    let items = r#"<DIDL-Lite xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/" xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/"><container id="SQ:12" parentID="SQ:" restricted="true" childCount="2"><dc:title>Saved</dc:title><res protocolInfo="file:*:audio/mpegurl:*">file:///jffs/settings/savedqueues.rsq#12</res><upnp:class>object.container.playlistContainer</upnp:class></container></DIDL-Lite>"#;
+
+    format!(
+        "<Result>{}</Result><NumberReturned>1</NumberReturned><TotalMatches>1</TotalMatches><UpdateID>1</UpdateID>",
+        escape(items)
+    )
+}
+
+fn queue_browse() -> String {
+    let items = r#"<DIDL-Lite xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/" xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/"><item id="Q:0/1" parentID="Q:0" restricted="true"><res duration="0:04:12">x-file-cifs://nas/music/one.flac</res><dc:title>One</dc:title><dc:creator>Artist</dc:creator><upnp:album>Album</upnp:album></item><item id="Q:0/2" parentID="Q:0" restricted="true"><res duration="0:02:58">x-file-cifs://nas/music/two.flac</res><dc:title>Two</dc:title><dc:creator>Artist</dc:creator><upnp:album>Album</upnp:album></item></DIDL-Lite>"#;
+
+    format!(
+        "<Result>{}</Result><NumberReturned>2</NumberReturned><TotalMatches>2</TotalMatches><UpdateID>1</UpdateID>",
+        escape(items)
+    )
+}
+
+const DEVICE_DESCRIPTION: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<root xmlns="urn:schemas-upnp-org:device-1-0">
+  <specVersion><major>1</major><minor>0</minor></specVersion>
+  <device>
+    <deviceType>urn:schemas-upnp-org:device:ZonePlayer:1</deviceType>
+    <friendlyName>127.0.0.1 - Sonos One - RINCON_000E58000000001400</friendlyName>
+    <modelNumber>S13</modelNumber>
+    <modelName>Sonos One</modelName>
+    <softwareVersion>56.0-76060</softwareVersion>
+    <hardwareVersion>1.20.1.6-2</hardwareVersion>
+    <serialNum>00-0E-58-00-00-00:0</serialNum>
+    <UDN>uuid:RINCON_000E58000000001400</UDN>
+    <roomName>Living Room</roomName>
+  </device>
+</root>"#;
+
+const RENDERING_CONTROL_SCPD: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<scpd xmlns="urn:schemas-upnp-org:service-1-0">
+  <specVersion><major>1</major><minor>0</minor></specVersion>
+  <serviceStateTable>
+    <stateVariable sendEvents="no">
+      <name>Bass</name>
+      <dataType>i2</dataType>
+      <allowedValueRange><minimum>-10</minimum><maximum>10</maximum><step>1</step></allowedValueRange>
+    </stateVariable>
+    <stateVariable sendEvents="no">
+      <name>Volume</name>
+      <dataType>ui2</dataType>
+      <allowedValueRange><minimum>0</minimum><maximum>100</maximum><step>1</step></allowedValueRange>
+    </stateVariable>
+  </serviceStateTable>
+</scpd>"#;
+
+#[tokio::test]
+async fn device_description() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+
+    assert_eq!(speaker.uuid, UUID);
+    assert_eq!(speaker.name, "Living Room");
+    assert_eq!(speaker.model, "Sonos One");
+    assert_eq!(speaker.model_number, "S13");
+    assert_eq!(speaker.software_version, "56.0-76060");
+    assert_eq!(speaker.port, mock.port);
+    assert!(mock.was_requested("/xml/device_description.xml"));
+    assert!(!speaker.https);
+}
+
+#[tokio::test]
+async fn volume() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+
+    assert_eq!(speaker.volume().await.expect("Failed to get volume"), 27);
+
+    let call = mock.last_call("GetVolume");
+    assert!(call.contains(r#"<u:GetVolume xmlns:u="urn:schemas-upnp-org:service:RenderingControl:1">"#));
+    assert!(call.contains("<Channel>Master</Channel>"));
+}
+
+#[tokio::test]
+async fn transport_state() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+
+    assert_eq!(
+        speaker.transport_state().await.expect("Failed to get transport state"),
+        TransportState::PausedPlayback
+    );
+    assert!(mock.last_call("GetTransportInfo").contains("<InstanceID>0</InstanceID>"));
+}
+
+#[tokio::test]
+async fn track() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let track = speaker.track().await.expect("Failed to get track");
+
+    assert_eq!(track.title, "Song");
+    assert_eq!(track.artist, "Artist");
+    assert_eq!(track.album.as_deref(), Some("Album"));
+    assert_eq!(track.queue_position, 3);
+    assert_eq!(track.uri, "x-file-cifs://nas/music/song.flac");
+    assert_eq!(
+        track.album_art_uri.as_deref(),
+        Some("/getaa?u=x-file-cifs%3a%2f%2fnas%2fmusic%2fsong.flac")
+    );
+    assert_eq!(track.duration, Duration::from_secs(210));
+    assert_eq!(track.running_time, Duration::from_secs(65));
+}
+
+#[tokio::test]
+async fn queue_list() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let queue = speaker.queue().list().await.expect("Failed to list queue");
+
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue[0].position, 1);
+    assert_eq!(queue[0].title, "One");
+    assert_eq!(queue[1].position, 2);
+    assert_eq!(queue[1].duration, Duration::from_secs(178));
+
+    let call = mock.last_call("Browse");
+    assert!(call.contains("<ObjectID>Q:0</ObjectID>"));
+    assert!(call.contains("<BrowseFlag>BrowseDirectChildren</BrowseFlag>"));
+}
+
+#[tokio::test]
+async fn browse_content_directory() {
+    use futures::TryStreamExt;
+
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let items: Vec<_> = speaker
+        .browse_content_directory("Q:0")
+        .try_collect()
+        .await
+        .expect("Failed to browse content directory");
+
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].name, "item");
+    assert_eq!(items[1].attributes.get("id").map(String::as_str), Some("Q:0/2"));
+    assert!(mock.last_call("Browse").contains("<StartingIndex>0</StartingIndex>"));
+}
+
+#[tokio::test]
+async fn input_av_source() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let source = speaker.get_input_av_source().await.expect("Failed to get input source");
+
+    assert_eq!(source.source_type, AVSourceType::LineIn);
+    assert_eq!(source.description, "Kitchen");
+}
+
+#[tokio::test]
+async fn set_unsupported_input_av_source() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let err = speaker
+        .set_input_av_source(AVSourceType::Hdmi)
+        .await
+        .expect_err("A One shouldn't have an HDMI input");
+
+    assert!(matches!(
+        err.downcast_ref::<SonosError>(),
+        Some(SonosError::UnsupportedInput(AVSourceType::Hdmi))
+    ));
+    assert!(!mock.requests.lock().unwrap().iter().any(|request| request.action.is_some()));
+}
+
+#[tokio::test]
+async fn bass_capabilities() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let bass = speaker.get_bass_capabilities().await.expect("Failed to get bass capabilities");
+
+    assert!(bass.supported);
+    assert_eq!((bass.min, bass.max), (-10, 10));
+    assert!(mock.was_requested("/xml/RenderingControl1.xml"));
+}
+
+#[tokio::test]
+async fn audio_delay() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+
+    assert_eq!(
+        speaker.get_audio_delay().await.expect("Failed to get audio delay"),
+        Duration::from_millis(40)
+    );
+    assert!(mock.last_call("GetEQ").contains("<EQType>AudioDelay</EQType>"));
+
+    speaker
+        .set_audio_delay(Duration::from_millis(120))
+        .await
+        .expect("Failed to set audio delay");
+    assert!(mock.last_call("SetEQ").contains("<DesiredValue>120</DesiredValue>"));
+}
+
+#[tokio::test]
+async fn av_transport_subscribe() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let sid = speaker
+        .av_transport_subscribe("http://127.0.0.1:3400/notify", 300)
+        .await
+        .expect("Failed to subscribe");
+
+    assert_eq!(sid, format!("uuid:{}_sub0000000001", UUID));
+    assert!(mock.was_requested("/MediaRenderer/AVTransport/Event"));
+}
+
+#[tokio::test]
+async fn rendering_control_subscribe() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let sid = speaker
+        .rendering_control_subscribe("http://127.0.0.1:3400/notify", 300)
+        .await
+        .expect("Failed to subscribe");
+
+    assert_eq!(sid, format!("uuid:{}_sub0000000001", UUID));
+    assert!(mock.was_requested("/MediaRenderer/RenderingControl/Event"));
+}
+
+#[tokio::test]
+async fn audio_clip_needs_s2() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let err = speaker
+        .play_audio_clip("http://127.0.0.1/chime.mp3", Some(30))
+        .await
+        .expect_err("Audio clips shouldn't be played on S1 firmware");
+
+    assert!(matches!(
+        err.downcast_ref::<SonosError>(),
+        Some(SonosError::NotSupportedByDevice(SonosFeature::AudioClip))
+    ));
+}
+
+#[tokio::test]
+async fn cancel_audio_clip() {
+    let mock = MockSpeaker::start();
+    let mut speaker = mock.speaker().await;
+    speaker.software_version = "63.2-88230".to_string();
+
+    let id = speaker
+        .play_audio_clip("http://127.0.0.1/chime.mp3", None)
+        .await
+        .expect("Failed to play audio clip");
+    assert_eq!(id, "clip-7");
+
+    speaker.cancel_audio_clip(&id).await.expect("Failed to cancel audio clip");
+    assert!(mock.last_call("CancelAudioClip").contains("<Id>clip-7</Id>"));
+}
+
+#[tokio::test]
+async fn play_container() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let uri = format!("x-rincon-playlist:{}#A:ALBUM/Album", UUID);
+
+    speaker
+        .av_transport_play_container(&uri, QueuePolicy::InsertNext)
+        .await
+        .expect("Failed to play container");
+
+    let call = mock.last_call("AddURIToQueue");
+    assert!(call.contains(&format!("<EnqueuedURI>{}</EnqueuedURI>", uri)));
+    assert!(call.contains("<EnqueueAsNext>1</EnqueueAsNext>"));
+    assert!(mock.last_call("Seek").contains("<Target>3</Target>"));
+    assert!(!mock.requests.lock().unwrap().iter().any(|r| r.action.as_deref() == Some("RemoveAllTracksFromQueue")));
+
+    speaker
+        .av_transport_play_container(&uri, QueuePolicy::Replace)
+        .await
+        .expect("Failed to play container");
+    mock.last_call("RemoveAllTracksFromQueue");
+    assert!(mock.last_call("AddURIToQueue").contains("<EnqueueAsNext>0</EnqueueAsNext>"));
+}
+
+#[tokio::test]
+async fn connection_refused() {
+    let mock = MockSpeaker::start();
+    let mut speaker = mock.speaker().await;
+
+    // bind a port and let it go again so nothing is listening on it
+    speaker.port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap().local_addr().unwrap().port();
+
+    let err = speaker.volume().await.expect_err("Nothing should be listening");
+    assert!(matches!(err.downcast_ref::<SonosError>(), Some(SonosError::ConnectionRefused)));
+
+    let err = speaker.http_get("/status/perf").await.expect_err("Nothing should be listening");
+    assert!(matches!(err.downcast_ref::<SonosError>(), Some(SonosError::ConnectionRefused)));
+
+    let err = speaker
+        .av_transport_subscribe("http://127.0.0.1:3400/notify", 300)
+        .await
+        .expect_err("Nothing should be listening");
+    assert!(matches!(err.downcast_ref::<SonosError>(), Some(SonosError::ConnectionRefused)));
+
+    let err = SpeakerBuilder::new()
+        .port(speaker.port)
+        .build(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        .await
+        .expect_err("Nothing should be listening");
+    assert!(matches!(err.downcast_ref::<SonosError>(), Some(SonosError::ConnectionRefused)));
+}
+
+#[tokio::test]
+async fn soap_timeout() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+
+    let err = SoapRequest::for_service(&Service::RENDERING_CONTROL)
+        .action("Stall")
+        .timeout(Duration::from_millis(300))
+        .send(&speaker)
+        .await
+        .expect_err("The mock never replies to Stall");
+    assert!(matches!(err.downcast_ref::<SonosError>(), Some(SonosError::Timeout)));
+}
+
+#[tokio::test]
+async fn builder_timeout() {
+    // connections to a listener that's never accepted from are left waiting for a response
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+
+    let err = SpeakerBuilder::new()
+        .port(listener.local_addr().unwrap().port())
+        .timeout(Duration::from_millis(300))
+        .build(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        .await
+        .expect_err("Nothing ever replies");
+    assert!(matches!(err.downcast_ref::<SonosError>(), Some(SonosError::Timeout)));
+}
+
+#[tokio::test]
+async fn query_topology_for_uuid() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let member = speaker
+        .query_topology_for_uuid("RINCON_000E58000000001401")
+        .await
+        .expect("Failed to find speaker");
+
+    assert_eq!(member.name, "Kitchen");
+    assert!(!member.is_coordinator);
+    assert_eq!(member.ip, IpAddr::V4(Ipv4Addr::LOCALHOST));
+
+    let err = speaker.query_topology_for_uuid("RINCON_NOPE").await.expect_err("Speaker shouldn't be found");
+    assert!(matches!(err.downcast_ref::<SonosError>(), Some(SonosError::DeviceNotFound(_))));
+}
+
+#[tokio::test]
+async fn save_queue_plays_playlist() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+    let playlist = speaker.save_queue("Saved").await.expect("Failed to save queue");
+
+    assert_eq!(playlist.id, "SQ:12");
+    assert!(mock.last_call("SaveQueue").contains("<Title>Saved</Title>"));
+    mock.last_call("RemoveAllTracksFromQueue");
+    assert!(mock
+        .last_call("AddURIToQueue")
+        .contains("<EnqueuedURI>file:///jffs/settings/savedqueues.rsq#12</EnqueuedURI>"));
+    assert!(mock.last_call("Seek").contains("<Target>3</Target>"));
+}
+
+#[tokio::test]
+async fn play_uri_and_wait_times_out() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+
+    // the mock always reports PAUSED_PLAYBACK, so the track never starts playing
+    let err = speaker
+        .play_uri_and_wait("x-file-cifs://nas/music/chime.mp3", Duration::from_millis(300))
+        .await
+        .expect_err("Track shouldn't have played");
+    assert!(matches!(err.downcast_ref::<SonosError>(), Some(SonosError::Timeout)));
+}
+
+#[tokio::test]
+async fn wait_for_state_times_out() {
+    let mock = MockSpeaker::start();
+    let speaker = mock.speaker().await;
+
+    let err = speaker
+        .wait_for_state(TransportState::Playing, Duration::from_millis(300))
+        .await
+        .expect_err("Speaker shouldn't have started playing");
+    assert!(matches!(err.downcast_ref::<SonosError>(), Some(SonosError::Timeout)));
+}
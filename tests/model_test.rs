@@ -0,0 +1,91 @@
+extern crate sonos;
+
+mod common;
+
+use sonos::{SonosFeature, Speaker};
+
+fn speaker(model_number: &str) -> Speaker {
+    common::speaker("Living Room", "Sonos", model_number)
+}
+
+#[test]
+fn soundbar_capabilities() {
+    let beam = speaker("S14");
+    assert!(beam.supports_tv());
+    assert!(beam.supports_night_mode());
+    assert!(beam.supports_speech_enhancement());
+    assert!(!beam.supports_line_in());
+}
+
+#[test]
+fn standalone_capabilities() {
+    let five = speaker("S21");
+    assert!(!five.supports_night_mode());
+    assert!(five.supports_line_in());
+}
+
+#[test]
+fn era_capabilities() {
+    for number in &["S39", "S41"] {
+        let era = speaker(number);
+        assert!(era.supports_line_in());
+        assert!(era.supports_feature(SonosFeature::LineIn));
+        assert!(!era.supports_tv());
+        assert!(!era.supports_night_mode());
+    }
+}
+
+#[test]
+fn unknown_model_capabilities() {
+    let unknown = speaker("S999");
+    assert!(!unknown.supports_tv());
+    assert!(!unknown.supports_line_in());
+}
+
+#[test]
+fn model_family() {
+    assert_eq!(speaker("S19").model_family(), sonos::ModelFamily::Soundbar);
+    assert_eq!(speaker("S27").model_family(), sonos::ModelFamily::PortableAmplified);
+    assert_eq!(speaker("Sub").model_family(), sonos::ModelFamily::Sub);
+    assert_eq!(speaker("S999").model_family(), sonos::ModelFamily::Unknown);
+}
+
+#[test]
+fn newer_model_families() {
+    assert_eq!(speaker("S39").model_family(), sonos::ModelFamily::StandaloneAmplified);
+    assert_eq!(speaker("S41").model_family(), sonos::ModelFamily::StandaloneAmplified);
+
+    for name in &["Sonos Sub Mini", "Sonos Sub"] {
+        let sub = Speaker {
+            model: name.to_string(),
+            ..speaker("S999")
+        };
+        assert_eq!(sub.model_family(), sonos::ModelFamily::Sub);
+        assert!(!sub.is_playback_device());
+    }
+}
+
+#[test]
+fn playback_devices() {
+    assert!(speaker("S14").is_playback_device());
+    assert!(speaker("S999").is_playback_device());
+    assert!(!speaker("ZB100").is_playback_device());
+    assert!(!speaker("WD100").is_playback_device());
+    assert!(!speaker("Sub").is_playback_device());
+}
+
+#[test]
+fn supports_feature() {
+    let arc = speaker("S19");
+    assert!(arc.supports_feature(SonosFeature::Playback));
+    assert!(arc.supports_feature(SonosFeature::Tv));
+    assert!(!arc.supports_feature(SonosFeature::LineIn));
+    assert!(!speaker("Sub").supports_feature(SonosFeature::StandaloneGroup));
+
+    assert!(!arc.supports_feature(SonosFeature::StatusJson));
+    let s2 = Speaker {
+        software_version: "80.1-55240".to_string(),
+        ..speaker("S19")
+    };
+    assert!(s2.supports_feature(SonosFeature::StatusJson));
+}
@@ -0,0 +1,18 @@
+#![cfg(feature = "chrono")]
+
+extern crate sonos;
+
+use sonos::RecentItem;
+
+#[test]
+fn last_played_at() {
+    let item = RecentItem {
+        title: "Morning Edition".to_string(),
+        uri: "x-sonosapi-stream:s24874?sid=254&flags=8224&sn=0".to_string(),
+        service_id: Some(254),
+        last_played: Some("2023-03-01T07:30:00+01:00".to_string()),
+    };
+
+    assert_eq!(item.last_played_at().unwrap().to_rfc3339(), "2023-03-01T06:30:00+00:00");
+    assert_eq!(RecentItem { last_played: Some("yesterday".to_string()), ..item }.last_played_at(), None);
+}
@@ -0,0 +1,30 @@
+extern crate sonos;
+
+use sonos::{Service, SpeakerEndpoint};
+
+#[test]
+fn service_paths_and_urn() {
+    assert_eq!(Service::AV_TRANSPORT.control_path(), "MediaRenderer/AVTransport/Control");
+    assert_eq!(Service::AV_TRANSPORT.event_path(), "MediaRenderer/AVTransport/Event");
+    assert_eq!(Service::AV_TRANSPORT.urn(), "urn:schemas-upnp-org:service:AVTransport:1");
+    assert_eq!(Service::QUEUE.urn(), "urn:schemas-sonos-com:service:Queue:1");
+}
+
+#[test]
+fn endpoint_url() {
+    let endpoint = SpeakerEndpoint::new("192.168.1.10".parse().unwrap(), "/status/topology");
+    assert_eq!(endpoint.url(), "http://192.168.1.10:1400/status/topology");
+
+    let endpoint = SpeakerEndpoint::new("fe80::1".parse().unwrap(), "xml/device_description.xml");
+    assert_eq!(endpoint.url(), "http://[fe80::1]:1400/xml/device_description.xml");
+}
+
+#[test]
+fn https_endpoint_url() {
+    let endpoint = SpeakerEndpoint {
+        https: true,
+        port: 1443,
+        ..SpeakerEndpoint::new("192.168.1.10".parse().unwrap(), "xml/device_description.xml")
+    };
+    assert_eq!(endpoint.url(), "https://192.168.1.10:1443/xml/device_description.xml");
+}